@@ -42,7 +42,7 @@ fn main() {
     for ground_state in exact_ground_states {
         println!(
             "\tEnergy: {} - State: {:?}",
-            ground_state.0, ground_state.1
+            ground_state.energy, ground_state.state
         );
     }
 
@@ -50,10 +50,10 @@ fn main() {
     let approximate_ground_states = ernst::solvers::simulated_annealing(&some_j, &some_h, None);
     let time_to_compute = now.elapsed().as_millis();
     println!("\nComplex spin glass ground state with simulated annealing - took: {} ms", time_to_compute);
-    for ground_state in approximate_ground_states {
+    for ground_state in approximate_ground_states.aggregate() {
         println!(
-            "\tEnergy: {} - State: {:?} - Found in sweep number: {}",
-            ground_state.0, ground_state.1, ground_state.2
+            "\tEnergy: {} - State: {:?} - Found {} times",
+            ground_state.energy, ground_state.state, ground_state.num_occurrences
         );
     }
 
@@ -69,12 +69,12 @@ fn main() {
     let ternary_or_ground_states = spin_network.find_all_ground_states(Some(interesting_spins.clone()));
     println!("\nTernary OR spin glass ground states:");
     for ground_state in ternary_or_ground_states {
-        println!("\tEnergy: {} - State: {:?}", ground_state.0, ground_state.1);
+        println!("\tEnergy: {} - State: {:?}", ground_state.energy, ground_state.state);
     }
 
     let copy_ground_states = spin_network.run_simulated_annealing(None, Some(interesting_spins));
     println!("\nTernary OR spin glass ground states with simulated annealing:");
-    for ground_state in copy_ground_states {
-        println!("\tEnergy: {} - State: {:?} - Found in sweep number: {}", ground_state.0, ground_state.1, ground_state.2);
+    for ground_state in copy_ground_states.aggregate() {
+        println!("\tEnergy: {} - State: {:?} - Found {} times", ground_state.energy, ground_state.state, ground_state.num_occurrences);
     }
 }
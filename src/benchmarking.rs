@@ -0,0 +1,373 @@
+use crate::generators::PlantedInstance;
+use crate::sample_set::{is_ground_state_energy, SampleSet};
+use crate::solvers::{
+    find_all_ground_states, simulated_annealing, SimulatedAnnealingConfiguration,
+};
+use crate::types::{Energy, ExternalMagneticField, Interactions, State};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Returns the fraction of `energies` that reach `ground_state_energy`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::benchmarking::success_probability;
+///
+/// assert_eq!(0.5, success_probability(&[-4.0, -2.0, -4.0, 0.0], -4.0));
+/// ```
+pub fn success_probability(energies: &[Energy], ground_state_energy: Energy) -> f32 {
+    if energies.is_empty() {
+        return 0.0;
+    }
+    let successes = energies
+        .iter()
+        .filter(|energy| is_ground_state_energy(**energy, ground_state_energy))
+        .count();
+
+    successes as f32 / energies.len() as f32
+}
+
+/// Returns the time it takes to find a ground state with probability `target_probability`, by repeating runs that
+/// take `time_per_run` each and succeed with probability `success_probability`, i.e.
+/// `time_per_run * ln(1 - target_probability) / ln(1 - success_probability)`. Returns None if no run ever succeeds.
+///
+/// ### Example
+///
+/// ```
+/// use std::time::Duration;
+/// use ernst::benchmarking::time_to_solution;
+///
+/// let time_per_run = Duration::from_millis(10);
+///
+/// let tts = time_to_solution(time_per_run, 0.9, 0.99).unwrap();
+///
+/// assert!((tts.as_secs_f64() - 0.02).abs() < 1e-6);
+/// assert_eq!(Some(time_per_run), time_to_solution(time_per_run, 1.0, 0.99));
+/// assert_eq!(None, time_to_solution(time_per_run, 0.0, 0.99));
+/// ```
+pub fn time_to_solution(
+    time_per_run: Duration,
+    success_probability: f32,
+    target_probability: f32,
+) -> Option<Duration> {
+    if success_probability <= 0.0 {
+        return None;
+    }
+    if success_probability >= target_probability {
+        return Some(time_per_run);
+    }
+    let repetitions =
+        (1.0 - target_probability as f64).ln() / (1.0 - success_probability as f64).ln();
+
+    Some(time_per_run.mul_f64(repetitions))
+}
+
+/// The outcome of benchmarking a solver on a planted instance.
+/// - `success_probability`: fraction of the runs that found a ground state
+/// - `time_per_run`: mean wall clock time of a single run
+/// - `time_to_solution`: time it takes to find a ground state with 99% probability, if any run found one
+pub struct Benchmark {
+    pub success_probability: f32,
+    pub time_per_run: Duration,
+    pub time_to_solution: Option<Duration>,
+}
+
+/// Runs [simulated_annealing] `runs` times on `planted_instance`, and measures how often it finds a ground state and
+/// how long it takes to do so. The seed of every run is derived from the seed of the configuration.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::benchmarking::benchmark_simulated_annealing;
+/// use ernst::generators::tile_planting;
+/// use ernst::solvers::SimulatedAnnealingConfiguration;
+///
+/// let planted_instance = tile_planting(4, 4, 0.5, 7);
+/// let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+///     initial_temperature: Some(5.0),
+///     final_temperature: Some(0.05),
+///     sweeps: 200,
+///     ..Default::default()
+/// };
+///
+/// let benchmark =
+///     benchmark_simulated_annealing(&planted_instance, 10, Some(&simulated_annealing_configuration));
+///
+/// assert!(benchmark.success_probability > 0.0);
+/// assert!(benchmark.time_to_solution.is_some());
+/// ```
+pub fn benchmark_simulated_annealing(
+    planted_instance: &PlantedInstance,
+    runs: usize,
+    configuration_override: Option<&SimulatedAnnealingConfiguration>,
+) -> Benchmark {
+    assert!(runs > 0, "At least one run is needed");
+    let config = configuration_override.cloned().unwrap_or_default();
+    let mut lowest_energies = vec![];
+
+    let now = Instant::now();
+    for run in 0..runs {
+        let run_configuration = SimulatedAnnealingConfiguration {
+            seed: config.seed.wrapping_add(run as u64),
+            ..config.clone()
+        };
+        let lowest_energy = simulated_annealing(
+            &planted_instance.interactions,
+            &planted_instance.external_magnetic_field,
+            Some(&run_configuration),
+        )
+        .lowest_energy()
+        .unwrap_or(Energy::INFINITY);
+        lowest_energies.push(lowest_energy);
+    }
+    let time_per_run = now.elapsed() / runs as u32;
+
+    let success_probability =
+        success_probability(&lowest_energies, planted_instance.ground_state_energy);
+
+    Benchmark {
+        success_probability,
+        time_per_run,
+        time_to_solution: time_to_solution(time_per_run, success_probability, 0.99),
+    }
+}
+
+/// How fairly a sampler samples the ground states of a spin glass.
+/// - `ground_states`: every ground state, in the order of [find_all_ground_states]
+/// - `frequencies`: fraction of the samples that hit a ground state which hit every one of `ground_states`
+/// - `ground_state_fraction`: fraction of the samples that hit a ground state at all
+/// - `kl_divergence`: Kullback-Leibler divergence of `frequencies` from the uniform distribution over `ground_states`,
+///   in nats, which is 0 for a fair sampler and `ln(ground_states.len())` for one that always hits the same ground
+///   state. None if no sample hit a ground state
+pub struct SamplingBias {
+    pub ground_states: Vec<State>,
+    pub frequencies: Vec<f32>,
+    pub ground_state_fraction: f32,
+    pub kl_divergence: Option<f64>,
+}
+
+/// Compares how often `samples` hit every one of `ground_states` with the uniform distribution over them. Every sample
+/// counts as many times as it occurred, and samples that aren't ground states only lower `ground_state_fraction`.
+///
+/// The divergence is estimated from a finite number of samples, so even a fair sampler has a divergence of about
+/// `(ground_states.len() - 1) / (2 * hits)`, where `hits` is the number of samples that hit a ground state.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::benchmarking::sampling_bias;
+/// use ernst::sample_set::{Sample, SampleSet};
+///
+/// let ground_states: SampleSet = vec![Sample::new(-1.0, vec![true, true]), Sample::new(-1.0, vec![false, false])]
+///     .into_iter()
+///     .collect();
+/// let samples: SampleSet = vec![
+///     Sample::new(-1.0, vec![true, true]),
+///     Sample::new(-1.0, vec![true, true]),
+///     Sample::new(1.0, vec![true, false]),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let sampling_bias = sampling_bias(&ground_states, &samples);
+///
+/// assert_eq!(vec![1.0, 0.0], sampling_bias.frequencies);
+/// assert!((sampling_bias.ground_state_fraction - 2.0 / 3.0).abs() < 1e-6);
+/// assert!((sampling_bias.kl_divergence.unwrap() - 2.0_f64.ln()).abs() < 1e-9);
+/// ```
+pub fn sampling_bias(ground_states: &SampleSet, samples: &SampleSet) -> SamplingBias {
+    assert!(
+        !ground_states.is_empty(),
+        "The sampling bias can only be measured over at least one ground state"
+    );
+    let index_of: HashMap<&State, usize> = ground_states
+        .iter()
+        .enumerate()
+        .map(|(index, ground_state)| (&ground_state.state, index))
+        .collect();
+    let mut hits = vec![0; ground_states.len()];
+    for sample in samples.iter() {
+        if let Some(index) = index_of.get(&sample.state) {
+            hits[*index] += sample.num_occurrences;
+        }
+    }
+    let total_hits: usize = hits.iter().sum();
+
+    let frequencies: Vec<f32> = hits
+        .iter()
+        .map(|hit| *hit as f32 / total_hits.max(1) as f32)
+        .collect();
+    let ground_state_fraction = total_hits as f32 / samples.num_occurrences().max(1) as f32;
+    let kl_divergence = (total_hits > 0).then(|| {
+        hits.iter()
+            .filter(|hit| **hit > 0)
+            .map(|hit| {
+                let probability = *hit as f64 / total_hits as f64;
+                probability * (probability * ground_states.len() as f64).ln()
+            })
+            .sum()
+    });
+
+    SamplingBias {
+        ground_states: ground_states
+            .iter()
+            .map(|sample| sample.state.clone())
+            .collect(),
+        frequencies,
+        ground_state_fraction,
+        kl_divergence,
+    }
+}
+
+/// Runs `sampler` `runs` times, passing it the index of every run so that it can derive its seed from it, and measures
+/// the [sampling_bias] of all of its samples over the ground states of the spin glass whose interaction terms and
+/// external magnetic field are given as the `interactions` and `external_magnetic_field` arguments, which are found
+/// exactly with [find_all_ground_states]. It's meant to detect gadgets, like the logic gates of
+/// [crate::nodelib::logic_gates], whose degenerate ground states a sampler doesn't hit equally often.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::benchmarking::measure_sampling_bias;
+/// use ernst::nodelib::logic_gates::AND;
+/// use ernst::solvers::{simulated_annealing, SimulatedAnnealingConfiguration};
+/// use ernst::spin_network::SpinNetwork;
+///
+/// let mut spin_network = SpinNetwork::new();
+/// let s0 = spin_network.add_input_node(0.0);
+/// let s1 = spin_network.add_input_node(0.0);
+/// spin_network.add_binary_node(s0, s1, &AND::default());
+///
+/// let sampling_bias = measure_sampling_bias(
+///     &spin_network.interactions,
+///     &spin_network.external_magnetic_field,
+///     10,
+///     |run| {
+///         let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+///             num_reads: 100,
+///             seed: run as u64,
+///             ..Default::default()
+///         };
+///         simulated_annealing(
+///             &spin_network.interactions,
+///             &spin_network.external_magnetic_field,
+///             Some(&simulated_annealing_configuration),
+///         )
+///     },
+/// );
+///
+/// // Every row of the truth table is a ground state
+/// assert_eq!(4, sampling_bias.ground_states.len());
+/// assert!(sampling_bias.ground_state_fraction > 0.99);
+/// assert!(sampling_bias.kl_divergence.unwrap() < 0.1);
+/// ```
+pub fn measure_sampling_bias(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    runs: usize,
+    mut sampler: impl FnMut(usize) -> SampleSet,
+) -> SamplingBias {
+    assert!(runs > 0, "At least one run is needed");
+    let ground_states = find_all_ground_states(interactions, external_magnetic_field);
+    let samples: SampleSet = (0..runs).flat_map(|run| sampler(run).samples).collect();
+
+    sampling_bias(&ground_states, &samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::benchmarking::{
+        benchmark_simulated_annealing, measure_sampling_bias, success_probability,
+    };
+    use crate::generators::tile_planting;
+    use crate::sample_set::{Sample, SampleSet};
+    use crate::solvers::SimulatedAnnealingConfiguration;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_success_probability_tolerates_rounding() {
+        assert_eq!(1.0, success_probability(&[-100.00001, -99.99999], -100.0));
+        assert_eq!(0.0, success_probability(&[-99.0], -100.0));
+        assert_eq!(0.0, success_probability(&[], -100.0));
+    }
+
+    #[test]
+    fn test_benchmark_simulated_annealing() {
+        let planted_instance = tile_planting(8, 8, 0.0, 7);
+        let too_short = SimulatedAnnealingConfiguration {
+            sweeps: 0,
+            num_reads: 1,
+            ..Default::default()
+        };
+
+        let benchmark = benchmark_simulated_annealing(&planted_instance, 5, Some(&too_short));
+
+        assert_eq!(0.0, benchmark.success_probability);
+        assert!(benchmark.time_to_solution.is_none());
+
+        // Sometimes, but not always, reaches the planted energy, so that a run has to be repeated to be 99% sure
+        let short_enough = SimulatedAnnealingConfiguration {
+            initial_temperature: Some(5.0),
+            final_temperature: Some(0.05),
+            sweeps: 10,
+            num_reads: 1,
+            ..Default::default()
+        };
+
+        let benchmark = benchmark_simulated_annealing(&planted_instance, 10, Some(&short_enough));
+
+        assert!(benchmark.success_probability > 0.0 && benchmark.success_probability < 0.99);
+        let repetitions = benchmark.time_to_solution.unwrap().as_secs_f64()
+            / benchmark.time_per_run.as_secs_f64();
+        let expected_repetitions =
+            0.01_f64.ln() / (1.0 - benchmark.success_probability as f64).ln();
+        assert!(repetitions.is_finite());
+        assert!((repetitions - expected_repetitions).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_measure_sampling_bias_of_fair_and_biased_samplers() {
+        // Three free spins, whose eight states are all ground states
+        let interactions = vec![];
+        let external_magnetic_field = vec![0.0; 3];
+        let random_state = |rng: &mut StdRng| (0..3).map(|_| rng.gen::<bool>()).collect();
+
+        let fair_sampling_bias =
+            measure_sampling_bias(&interactions, &external_magnetic_field, 4, |run| {
+                let mut rng = StdRng::seed_from_u64(run as u64);
+                (0..2000)
+                    .map(|_| Sample::new(0.0, random_state(&mut rng)))
+                    .collect()
+            });
+        // Half of the samples of this sampler always land on the same state
+        let biased_sampling_bias =
+            measure_sampling_bias(&interactions, &external_magnetic_field, 4, |run| {
+                let mut rng = StdRng::seed_from_u64(run as u64);
+                let mut samples: SampleSet = (0..1000)
+                    .map(|_| Sample::new(0.0, random_state(&mut rng)))
+                    .collect();
+                samples.push(Sample {
+                    num_occurrences: 1000,
+                    ..Sample::new(0.0, vec![false; 3])
+                });
+                samples
+            });
+
+        assert_eq!(8, fair_sampling_bias.ground_states.len());
+        assert_eq!(1.0, fair_sampling_bias.ground_state_fraction);
+        assert!(fair_sampling_bias
+            .frequencies
+            .iter()
+            .all(|frequency| (frequency - 0.125).abs() < 0.02));
+        assert!(fair_sampling_bias.kl_divergence.unwrap() < 0.005);
+        let biased_index = biased_sampling_bias
+            .ground_states
+            .iter()
+            .position(|state| *state == vec![false; 3])
+            .unwrap();
+        assert!((biased_sampling_bias.frequencies[biased_index] - 0.5625).abs() < 0.02);
+        assert!(biased_sampling_bias.kl_divergence.unwrap() > 0.2);
+    }
+}
@@ -0,0 +1,798 @@
+pub mod topologies;
+
+use crate::sample_set::{Sample, SampleSet};
+use crate::solvers::energy;
+use crate::spin_network::SpinNetwork;
+use crate::types::{
+    Chain, Coupler, Energy, ExternalMagneticField, Interactions, Qubit, SpinIndex, State,
+};
+use ordered_float::OrderedFloat;
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+// Every chain sharing a qubit multiplies its weight by this much. It's fixed, rather than growing with the size of the
+// hardware graph, so that the weight of a qubit shared by all of the 64 chains it's capped at stays finite.
+const CONGESTION_PENALTY_BASE: f64 = 256.0;
+
+/// A HardwareGraph is the sparse graph of qubits and couplers of a quantum annealer. See [topologies] for generators
+/// of the most common ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HardwareGraph {
+    pub num_qubits: usize,
+    pub couplers: Vec<Coupler>,
+}
+
+impl HardwareGraph {
+    /// Creates a new HardwareGraph. Couplers are normalized so that the smallest qubit comes first, and duplicates are
+    /// removed.
+    pub fn new(num_qubits: usize, couplers: Vec<Coupler>) -> Self {
+        let mut couplers: Vec<Coupler> = couplers
+            .into_iter()
+            .map(|(left_qubit, right_qubit)| {
+                assert!(
+                    left_qubit < num_qubits && right_qubit < num_qubits,
+                    "The coupler ({}, {}) refers to a qubit that does not exist",
+                    left_qubit,
+                    right_qubit
+                );
+                (left_qubit.min(right_qubit), left_qubit.max(right_qubit))
+            })
+            .filter(|(left_qubit, right_qubit)| left_qubit != right_qubit)
+            .collect();
+        couplers.sort_unstable();
+        couplers.dedup();
+
+        HardwareGraph {
+            num_qubits,
+            couplers,
+        }
+    }
+    /// Returns the neighbours of every qubit.
+    pub fn adjacency(&self) -> Vec<Vec<Qubit>> {
+        let mut adjacency = vec![vec![]; self.num_qubits];
+        for (left_qubit, right_qubit) in &self.couplers {
+            adjacency[*left_qubit].push(*right_qubit);
+            adjacency[*right_qubit].push(*left_qubit);
+        }
+
+        adjacency
+    }
+}
+
+/// An Embedding maps every logical spin, by its index, to the chain of physical qubits that represents it. It also
+/// keeps the couplers of the hardware graph that are available between the qubits in use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Embedding {
+    pub chains: Vec<Chain>,
+    pub couplers: Vec<Coupler>,
+}
+
+impl Embedding {
+    /// Creates a new Embedding out of the `chains`, keeping only the couplers of the `hardware_graph` that connect
+    /// qubits in use.
+    pub fn new(chains: Vec<Chain>, hardware_graph: &HardwareGraph) -> Self {
+        let mut in_use = vec![false; hardware_graph.num_qubits];
+        for qubit in chains.iter().flatten() {
+            in_use[*qubit] = true;
+        }
+        let couplers = hardware_graph
+            .couplers
+            .iter()
+            .filter(|(left_qubit, right_qubit)| in_use[*left_qubit] && in_use[*right_qubit])
+            .cloned()
+            .collect();
+
+        Embedding { chains, couplers }
+    }
+    /// Returns the total number of physical qubits used.
+    pub fn num_qubits(&self) -> usize {
+        self.chains.iter().map(|chain| chain.len()).sum()
+    }
+    /// Returns the length of the longest chain.
+    pub fn max_chain_length(&self) -> usize {
+        self.chains
+            .iter()
+            .map(|chain| chain.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// How to decide the value of a logical spin whose chain of qubits does not agree on a single value.
+/// - `MajorityVote`: takes the value of most qubits in the chain, ties are broken in favour of the first qubit
+/// - `Discard`: drops every sample with at least one broken chain
+/// - `MinimizeEnergy`: starts from the majority vote, and then greedily sets every broken chain to the value that
+///   minimizes the energy of the logical spin glass
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChainBreakResolution {
+    MajorityVote,
+    Discard,
+    MinimizeEnergy,
+}
+
+/// An EmbeddedSpinNetwork is the physical spin glass that results from embedding a [SpinNetwork] onto a
+/// [HardwareGraph]. See [SpinNetwork::embed].
+///
+/// The physical spins are indexed contiguously, chain after chain, and `qubits` maps every one of them back to the
+/// hardware qubit it stands for. `chains` has, for every logical spin, the indices of its physical spins.
+pub struct EmbeddedSpinNetwork {
+    pub spin_network: SpinNetwork,
+    pub qubits: Vec<Qubit>,
+    pub chains: Vec<Vec<SpinIndex>>,
+    pub(crate) logical_interactions: Interactions,
+    pub(crate) logical_external_magnetic_field: ExternalMagneticField,
+}
+
+impl EmbeddedSpinNetwork {
+    /// Maps `sample_set` of the physical spin glass back to the logical one, resolving broken chains according to
+    /// `chain_break_resolution`. Every unembedded sample comes with its logical energy, and the fraction of chains that
+    /// were broken; all the other metadata, and the traces of the physical reads, are kept.
+    pub fn unembed(
+        &self,
+        sample_set: &SampleSet,
+        chain_break_resolution: ChainBreakResolution,
+    ) -> SampleSet {
+        let mut logical_neighbours: Vec<Vec<(SpinIndex, Energy)>> = vec![vec![]; self.chains.len()];
+        for (i, j, interaction_strength) in &self.logical_interactions {
+            logical_neighbours[*i].push((*j, *interaction_strength));
+            logical_neighbours[*j].push((*i, *interaction_strength));
+        }
+
+        let samples = sample_set
+            .iter()
+            .filter_map(|physical_sample| {
+                let sample = &physical_sample.state;
+                let mut broken_chains = vec![];
+                let mut state: State = self
+                    .chains
+                    .iter()
+                    .enumerate()
+                    .map(|(spin_index, chain)| {
+                        let ups = chain
+                            .iter()
+                            .filter(|physical_spin| sample[**physical_spin])
+                            .count();
+                        if ups != 0 && ups != chain.len() {
+                            broken_chains.push(spin_index);
+                        }
+                        if 2 * ups == chain.len() {
+                            sample[chain[0]]
+                        } else {
+                            2 * ups > chain.len()
+                        }
+                    })
+                    .collect();
+                let chain_break_fraction = broken_chains.len() as f32 / self.chains.len() as f32;
+
+                match chain_break_resolution {
+                    ChainBreakResolution::Discard if !broken_chains.is_empty() => return None,
+                    ChainBreakResolution::MinimizeEnergy => {
+                        for spin_index in broken_chains {
+                            let local_field: Energy = self.logical_external_magnetic_field
+                                [spin_index]
+                                + logical_neighbours[spin_index]
+                                    .iter()
+                                    .map(|(neighbour, interaction_strength)| {
+                                        if state[*neighbour] {
+                                            *interaction_strength
+                                        } else {
+                                            -*interaction_strength
+                                        }
+                                    })
+                                    .sum::<Energy>();
+                            if local_field != 0.0 {
+                                state[spin_index] = local_field > 0.0;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+
+                Some(Sample {
+                    energy: energy(
+                        &self.logical_interactions,
+                        &self.logical_external_magnetic_field,
+                        &state,
+                    ),
+                    state,
+                    chain_break_fraction: Some(chain_break_fraction),
+                    ..physical_sample.clone()
+                })
+            })
+            .collect();
+
+        SampleSet {
+            samples,
+            traces: sample_set.traces.clone(),
+        }
+    }
+}
+
+fn logical_adjacency(interactions: &Interactions, num_spins: usize) -> Vec<Vec<SpinIndex>> {
+    let mut adjacency: Vec<Vec<SpinIndex>> = vec![vec![]; num_spins];
+    for (left_spin_index, right_spin_index, interaction_strength) in interactions {
+        if left_spin_index == right_spin_index || *interaction_strength == 0.0 {
+            continue;
+        }
+        adjacency[*left_spin_index].push(*right_spin_index);
+        adjacency[*right_spin_index].push(*left_spin_index);
+    }
+    for neighbours in adjacency.iter_mut() {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+
+    adjacency
+}
+
+/// Checks, without any device access, that `embedding` is a valid minor embedding of the spin glass whose interaction
+/// terms are `interactions` onto `hardware_graph`. That is, every one of the `num_spins` spins has a non-empty chain,
+/// chains are disjoint, every chain is connected, and every non-zero interaction is backed by at least one coupler.
+pub fn verify_embedding(
+    interactions: &Interactions,
+    num_spins: usize,
+    embedding: &Embedding,
+    hardware_graph: &HardwareGraph,
+) -> bool {
+    if embedding.chains.len() != num_spins {
+        return false;
+    }
+
+    let mut owner: Vec<Option<SpinIndex>> = vec![None; hardware_graph.num_qubits];
+    for (spin_index, chain) in embedding.chains.iter().enumerate() {
+        if chain.is_empty() {
+            return false;
+        }
+        for qubit in chain {
+            if *qubit >= hardware_graph.num_qubits || owner[*qubit].is_some() {
+                return false;
+            }
+            owner[*qubit] = Some(spin_index);
+        }
+    }
+
+    let adjacency = hardware_graph.adjacency();
+    for (spin_index, chain) in embedding.chains.iter().enumerate() {
+        let mut visited = vec![chain[0]];
+        let mut stack = vec![chain[0]];
+        while let Some(qubit) = stack.pop() {
+            for neighbour in &adjacency[qubit] {
+                if owner[*neighbour] == Some(spin_index) && !visited.contains(neighbour) {
+                    visited.push(*neighbour);
+                    stack.push(*neighbour);
+                }
+            }
+        }
+        if visited.len() != chain.len() {
+            return false;
+        }
+    }
+
+    let mut coupled: ahash::HashSet<(SpinIndex, SpinIndex)> = Default::default();
+    for (left_qubit, right_qubit) in &hardware_graph.couplers {
+        if let (Some(left_spin_index), Some(right_spin_index)) =
+            (owner[*left_qubit], owner[*right_qubit])
+        {
+            coupled.insert((
+                left_spin_index.min(right_spin_index),
+                left_spin_index.max(right_spin_index),
+            ));
+        }
+    }
+
+    logical_adjacency(interactions, num_spins)
+        .iter()
+        .enumerate()
+        .all(|(spin_index, neighbours)| {
+            neighbours.iter().all(|neighbour| {
+                coupled.contains(&(spin_index.min(*neighbour), spin_index.max(*neighbour)))
+            })
+        })
+}
+
+/// Parameters for the minor embedding heuristic.
+/// - `tries`: number of independent attempts, each starting from scratch
+/// - `rounds`: number of rip-up and reroute rounds, per try, used to remove overlaps between chains
+/// - `seed`: rng seed that ensures the whole process to be repeatable
+pub struct MinorEmbeddingConfiguration {
+    pub tries: usize,
+    pub rounds: usize,
+    pub seed: u64,
+}
+
+impl Default for MinorEmbeddingConfiguration {
+    fn default() -> Self {
+        MinorEmbeddingConfiguration {
+            tries: 10,
+            rounds: 100,
+            seed: 42,
+        }
+    }
+}
+
+struct ChainRouter<'a> {
+    adjacency: &'a Vec<Vec<Qubit>>,
+    usage: Vec<usize>,
+    history: Vec<f64>,
+}
+
+impl ChainRouter<'_> {
+    // Qubits that are used by other chains are exponentially more expensive, and those that have been overused in past
+    // rounds become increasingly so, which breaks the deadlocks of two chains fighting over the same spot.
+    fn weight(&self, qubit: Qubit) -> f64 {
+        (1.0 + self.history[qubit]) * CONGESTION_PENALTY_BASE.powi(self.usage[qubit].min(64) as i32)
+    }
+    // Cost of reaching every qubit from `chain`, not counting the weight of the destination qubit, alongside the
+    // predecessor of every qubit on its cheapest path.
+    fn distances_from(&self, chain: &Chain) -> (Vec<f64>, Vec<Option<Qubit>>) {
+        let num_qubits = self.adjacency.len();
+        let mut distances = vec![f64::INFINITY; num_qubits];
+        let mut predecessors = vec![None; num_qubits];
+        let mut is_source = vec![false; num_qubits];
+        let mut heap = BinaryHeap::new();
+        for qubit in chain {
+            distances[*qubit] = 0.0;
+            is_source[*qubit] = true;
+            heap.push(Reverse((OrderedFloat(0.0), *qubit)));
+        }
+
+        while let Some(Reverse((OrderedFloat(distance), qubit))) = heap.pop() {
+            if distance > distances[qubit] {
+                continue;
+            }
+            let step_cost = if is_source[qubit] {
+                0.0
+            } else {
+                self.weight(qubit)
+            };
+            for neighbour in &self.adjacency[qubit] {
+                let candidate_distance = distance + step_cost;
+                if candidate_distance < distances[*neighbour] {
+                    distances[*neighbour] = candidate_distance;
+                    predecessors[*neighbour] = Some(qubit);
+                    heap.push(Reverse((OrderedFloat(candidate_distance), *neighbour)));
+                }
+            }
+        }
+
+        (distances, predecessors)
+    }
+    fn route(&mut self, neighbour_chains: Vec<&Chain>, rng: &mut StdRng) -> Chain {
+        let num_qubits = self.adjacency.len();
+        let mut chain: Chain = vec![];
+        if neighbour_chains.is_empty() {
+            let lowest_usage = *self.usage.iter().min().unwrap();
+            let candidates: Vec<Qubit> = (0..num_qubits)
+                .filter(|qubit| {
+                    self.usage[*qubit] == lowest_usage && !self.adjacency[*qubit].is_empty()
+                })
+                .collect();
+            let root = if candidates.is_empty() {
+                rng.gen_range(0..num_qubits)
+            } else {
+                candidates[rng.gen_range(0..candidates.len())]
+            };
+            chain.push(root);
+        } else {
+            let routes: Vec<(Vec<f64>, Vec<Option<Qubit>>)> = neighbour_chains
+                .iter()
+                .map(|neighbour_chain| self.distances_from(neighbour_chain))
+                .collect();
+            let costs: Vec<f64> = (0..num_qubits)
+                .map(|qubit| {
+                    self.weight(qubit)
+                        + routes
+                            .iter()
+                            .map(|(distances, _)| distances[qubit])
+                            .sum::<f64>()
+                })
+                .collect();
+            let lowest_cost = costs.iter().cloned().fold(f64::INFINITY, f64::min);
+            let candidates: Vec<Qubit> = (0..num_qubits)
+                .filter(|qubit| costs[*qubit] <= lowest_cost)
+                .collect();
+            let root = candidates[rng.gen_range(0..candidates.len())];
+
+            chain.push(root);
+            for (_, predecessors) in &routes {
+                let mut qubit = root;
+                while let Some(predecessor) = predecessors[qubit] {
+                    if predecessors[predecessor].is_none() {
+                        break;
+                    }
+                    if !chain.contains(&predecessor) {
+                        chain.push(predecessor);
+                    }
+                    qubit = predecessor;
+                }
+            }
+        }
+
+        for qubit in &chain {
+            self.usage[*qubit] += 1;
+        }
+
+        chain
+    }
+    fn rip_up(&mut self, chain: &Chain) {
+        for qubit in chain {
+            self.usage[*qubit] -= 1;
+        }
+    }
+    fn is_overlapping(&mut self) -> bool {
+        let mut is_overlapping = false;
+        for (qubit, usage) in self.usage.iter().enumerate() {
+            if *usage > 1 {
+                self.history[qubit] = 2.0 * self.history[qubit] + 1.0;
+                is_overlapping = true;
+            }
+        }
+
+        is_overlapping
+    }
+}
+
+/// Heuristically finds a minor embedding, in the style of minorminer, of the spin glass whose interaction terms are
+/// `interactions` onto `hardware_graph`. Every one of the `num_spins` logical spins gets its own chain of qubits.
+///
+/// Chains are first greedily routed through shortest paths, allowing them to overlap at an exponentially increasing
+/// cost, and then repeatedly ripped up and rerouted until no qubit is shared. Qubits that remain shared across rounds
+/// accumulate a history cost, as in negotiated congestion routing. It returns `None` if no valid embedding
+/// was found.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::embedding::{find_embedding, verify_embedding};
+/// use ernst::embedding::topologies::chimera_graph;
+///
+/// // A triangle can't be natively represented on a bipartite graph, so at least one chain has to be longer than one
+/// let triangle = vec![(0, 1, 1.0), (1, 2, 1.0), (0, 2, 1.0)];
+/// let hardware_graph = chimera_graph(1, 1, 4);
+///
+/// let embedding = find_embedding(&triangle, 3, &hardware_graph, None).unwrap();
+///
+/// assert!(verify_embedding(&triangle, 3, &embedding, &hardware_graph));
+/// assert!(embedding.num_qubits() > 3);
+/// ```
+pub fn find_embedding(
+    interactions: &Interactions,
+    num_spins: usize,
+    hardware_graph: &HardwareGraph,
+    configuration_override: Option<&MinorEmbeddingConfiguration>,
+) -> Option<Embedding> {
+    let default_configuration = MinorEmbeddingConfiguration::default();
+    let config = configuration_override.unwrap_or(&default_configuration);
+    if num_spins == 0 || num_spins > hardware_graph.num_qubits {
+        return None;
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let logical_adjacency = logical_adjacency(interactions, num_spins);
+    let adjacency = hardware_graph.adjacency();
+
+    for _ in 0..config.tries {
+        let mut router = ChainRouter {
+            adjacency: &adjacency,
+            usage: vec![0; hardware_graph.num_qubits],
+            history: vec![0.0; hardware_graph.num_qubits],
+        };
+        let mut chains: Vec<Chain> = vec![vec![]; num_spins];
+
+        // Breadth-first order from random roots keeps neighbouring spins close to each other
+        let mut order: Vec<SpinIndex> = Vec::with_capacity(num_spins);
+        let mut queued = vec![false; num_spins];
+        let mut roots: Vec<SpinIndex> = (0..num_spins).collect();
+        roots.shuffle(&mut rng);
+        for root in roots {
+            if queued[root] {
+                continue;
+            }
+            queued[root] = true;
+            let mut queue = VecDeque::from([root]);
+            while let Some(spin_index) = queue.pop_front() {
+                order.push(spin_index);
+                let mut neighbours = logical_adjacency[spin_index].clone();
+                neighbours.shuffle(&mut rng);
+                for neighbour in neighbours {
+                    if !queued[neighbour] {
+                        queued[neighbour] = true;
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        for spin_index in &order {
+            let neighbour_chains = logical_adjacency[*spin_index]
+                .iter()
+                .map(|neighbour| &chains[*neighbour])
+                .filter(|chain| !chain.is_empty())
+                .collect();
+            chains[*spin_index] = router.route(neighbour_chains, &mut rng);
+        }
+
+        for _ in 0..config.rounds {
+            if !router.is_overlapping() {
+                break;
+            }
+            order.shuffle(&mut rng);
+            for spin_index in &order {
+                let chain = std::mem::take(&mut chains[*spin_index]);
+                router.rip_up(&chain);
+                let neighbour_chains = logical_adjacency[*spin_index]
+                    .iter()
+                    .map(|neighbour| &chains[*neighbour])
+                    .collect();
+                chains[*spin_index] = router.route(neighbour_chains, &mut rng);
+            }
+        }
+
+        let embedding = Embedding::new(chains, hardware_graph);
+        if verify_embedding(interactions, num_spins, &embedding, hardware_graph) {
+            return Some(embedding);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::embedding::topologies::{chimera_graph, pegasus_graph, zephyr_graph};
+    use crate::embedding::{
+        find_embedding, verify_embedding, ChainBreakResolution, ChainRouter, Embedding,
+        HardwareGraph,
+    };
+    use crate::nodelib::logic_gates::{AND, COPY, OR, XOR};
+    use crate::sample_set::{Sample, SampleSet};
+    use crate::solvers::{simulated_annealing, SimulatedAnnealingConfiguration};
+    use crate::spin_network::SpinNetwork;
+    use crate::types::Interactions;
+
+    fn complete_graph(n: usize) -> Interactions {
+        (0..n)
+            .flat_map(|i| ((i + 1)..n).map(move |j| (i, j, 1.0)))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_embedding() {
+        let hardware_graph = HardwareGraph::new(4, vec![(0, 1), (1, 2), (2, 3)]);
+        let interactions: Interactions = vec![(0, 1, 1.0)];
+
+        let valid = Embedding::new(vec![vec![0, 1], vec![2, 3]], &hardware_graph);
+        let overlapping = Embedding::new(vec![vec![0, 1], vec![1, 2]], &hardware_graph);
+        let disconnected = Embedding::new(vec![vec![0, 2], vec![3]], &hardware_graph);
+        let not_coupled = Embedding::new(vec![vec![0], vec![2, 3]], &hardware_graph);
+
+        assert!(verify_embedding(&interactions, 2, &valid, &hardware_graph));
+        assert!(!verify_embedding(
+            &interactions,
+            2,
+            &overlapping,
+            &hardware_graph
+        ));
+        assert!(!verify_embedding(
+            &interactions,
+            2,
+            &disconnected,
+            &hardware_graph
+        ));
+        assert!(!verify_embedding(
+            &interactions,
+            2,
+            &not_coupled,
+            &hardware_graph
+        ));
+    }
+
+    #[test]
+    fn test_find_embedding_complete_graphs() {
+        let chimera = chimera_graph(4, 4, 4);
+        let k8 = complete_graph(8);
+        let embedding = find_embedding(&k8, 8, &chimera, None).unwrap();
+        assert!(verify_embedding(&k8, 8, &embedding, &chimera));
+
+        let pegasus = pegasus_graph(4);
+        let k12 = complete_graph(12);
+        let embedding = find_embedding(&k12, 12, &pegasus, None).unwrap();
+        assert!(verify_embedding(&k12, 12, &embedding, &pegasus));
+
+        let zephyr = zephyr_graph(2, 4);
+        let embedding = find_embedding(&k12, 12, &zephyr, None).unwrap();
+        assert!(verify_embedding(&k12, 12, &embedding, &zephyr));
+    }
+
+    #[test]
+    fn test_find_embedding_logic_gates() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        let s2 = spin_network.add_input_node(0.0);
+        let or_output = spin_network.add_binary_node(s0, s1, &OR::default());
+        let and_output = spin_network.add_binary_node(or_output, s2, &AND::default());
+        spin_network.add_binary_node(and_output, s0, &XOR::default());
+
+        let chimera = chimera_graph(4, 4, 4);
+        let embedding = spin_network.find_embedding(&chimera, None).unwrap();
+
+        assert!(verify_embedding(
+            &spin_network.interactions,
+            spin_network.external_magnetic_field.len(),
+            &embedding,
+            &chimera
+        ));
+    }
+
+    #[test]
+    fn test_find_embedding_too_large() {
+        let chimera = chimera_graph(1, 1, 4);
+        let k9 = complete_graph(9);
+
+        assert_eq!(None, find_embedding(&k9, 9, &chimera, None));
+    }
+
+    #[test]
+    fn test_find_embedding_impossible() {
+        // There are as many qubits as spins, but a path has no triangle and no spare qubits to form a chain
+        let path = HardwareGraph::new(3, vec![(0, 1), (1, 2)]);
+        let triangle = complete_graph(3);
+
+        assert_eq!(None, find_embedding(&triangle, 3, &path, None));
+    }
+
+    #[test]
+    fn test_chain_router_weights_on_large_graphs() {
+        let num_qubits = 100_000;
+        let adjacency = vec![vec![]; num_qubits];
+        let mut router = ChainRouter {
+            adjacency: &adjacency,
+            usage: vec![0; num_qubits],
+            history: vec![0.0; num_qubits],
+        };
+        router.usage[1] = 1;
+        router.usage[2] = 2;
+        router.usage[3] = 64;
+
+        let weights: Vec<f64> = (0..4).map(|qubit| router.weight(qubit)).collect();
+
+        assert!(weights.iter().all(|weight| weight.is_finite()));
+        assert!(weights.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_embed() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(1.0);
+        spin_network.add_unary_node(s0, &COPY::default());
+        let hardware_graph = HardwareGraph::new(4, vec![(0, 1), (1, 2), (2, 3), (0, 3)]);
+        let embedding = Embedding::new(vec![vec![0, 1], vec![2, 3]], &hardware_graph);
+
+        let embedded_spin_network = spin_network.embed(&embedding, 3.0).unwrap();
+
+        assert_eq!(vec![0, 1, 2, 3], embedded_spin_network.qubits);
+        assert_eq!(vec![vec![0, 1], vec![2, 3]], embedded_spin_network.chains);
+        assert_eq!(
+            vec![0.5, 0.5, 0.0, 0.0],
+            embedded_spin_network.spin_network.external_magnetic_field
+        );
+        assert_eq!(
+            vec![(0, 1, 3.0), (2, 3, 3.0), (0, 3, 0.5), (1, 2, 0.5)],
+            embedded_spin_network.spin_network.interactions
+        );
+    }
+
+    #[test]
+    fn test_embed_rejects_invalid_embeddings() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(1.0);
+        spin_network.add_unary_node(s0, &COPY::default());
+        let hardware_graph = HardwareGraph::new(4, vec![(0, 1), (2, 3)]);
+
+        for chains in [
+            vec![vec![0, 1], vec![2, 3]],
+            vec![vec![0, 1]],
+            vec![vec![0, 2], vec![1]],
+        ] {
+            let embedding = Embedding::new(chains, &hardware_graph);
+
+            assert!(spin_network.embed(&embedding, 3.0).is_none());
+        }
+    }
+
+    #[test]
+    fn test_unembed_chain_break_resolution() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        spin_network.add_unary_node(s0, &COPY::default());
+        let hardware_graph = HardwareGraph::new(4, vec![(0, 1), (1, 2), (2, 3)]);
+        let embedding = Embedding::new(vec![vec![0, 1, 2], vec![3]], &hardware_graph);
+        let embedded_spin_network = spin_network.embed(&embedding, 1.0).unwrap();
+        let samples: SampleSet = vec![
+            Sample::new(0.0, vec![true, true, false, false]),
+            Sample::new(0.0, vec![true, true, true, true]),
+        ]
+        .into_iter()
+        .collect();
+        let energies_states_and_chain_breaks = |sample_set: SampleSet| -> Vec<_> {
+            sample_set
+                .into_iter()
+                .map(|sample| {
+                    (
+                        sample.energy,
+                        sample.state,
+                        sample.chain_break_fraction.unwrap(),
+                    )
+                })
+                .collect()
+        };
+
+        let majority_vote =
+            embedded_spin_network.unembed(&samples, ChainBreakResolution::MajorityVote);
+        let discard = embedded_spin_network.unembed(&samples, ChainBreakResolution::Discard);
+        let minimize_energy =
+            embedded_spin_network.unembed(&samples, ChainBreakResolution::MinimizeEnergy);
+
+        assert_eq!(
+            vec![(1.0, vec![true, false], 0.5), (-1.0, vec![true, true], 0.0)],
+            energies_states_and_chain_breaks(majority_vote)
+        );
+        assert_eq!(
+            vec![(-1.0, vec![true, true], 0.0)],
+            energies_states_and_chain_breaks(discard)
+        );
+        assert_eq!(
+            vec![
+                (-1.0, vec![false, false], 0.5),
+                (-1.0, vec![true, true], 0.0)
+            ],
+            energies_states_and_chain_breaks(minimize_energy)
+        );
+    }
+
+    #[test]
+    fn test_embed_and_unembed_with_simulated_annealing() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        let s2 = spin_network.add_input_node(0.0);
+        let or_output = spin_network.add_binary_node(s0, s1, &OR::default());
+        spin_network.add_binary_node(or_output, s2, &XOR::default());
+        let logical_ground_energy = spin_network
+            .find_all_ground_states(None)
+            .lowest_energy()
+            .unwrap();
+
+        let embedding = spin_network
+            .find_embedding(&chimera_graph(3, 3, 4), None)
+            .unwrap();
+        let embedded_spin_network = spin_network.embed(&embedding, 4.0).unwrap();
+        let configuration = SimulatedAnnealingConfiguration {
+            initial_temperature: Some(5.0),
+            final_temperature: Some(0.01),
+            sweeps: 1000,
+            seed: 42,
+            trace: false,
+            ..Default::default()
+        };
+        let physical_samples = simulated_annealing(
+            &embedded_spin_network.spin_network.interactions,
+            &embedded_spin_network.spin_network.external_magnetic_field,
+            Some(&configuration),
+        )
+        .lowest();
+
+        let logical_samples =
+            embedded_spin_network.unembed(&physical_samples, ChainBreakResolution::MajorityVote);
+
+        assert!(!logical_samples.is_empty());
+        for sample in logical_samples {
+            assert_eq!(logical_ground_energy, sample.energy);
+            assert_eq!(Some(0.0), sample.chain_break_fraction);
+        }
+    }
+}
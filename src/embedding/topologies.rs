@@ -0,0 +1,253 @@
+use crate::embedding::HardwareGraph;
+use crate::types::{Coupler, Qubit};
+
+/// Builds a Chimera-like hardware graph: a `rows` x `columns` grid of unit cells, each one being a complete bipartite
+/// graph K<sub>shore,shore</sub>. Vertical qubits couple to the vertical qubits of the cell below them, and horizontal
+/// qubits to the horizontal qubits of the cell to their right.
+///
+/// The qubit at row `i`, column `j`, orientation `u` (0 for vertical, 1 for horizontal) and index `k` has the label
+/// `((i * columns + j) * 2 + u) * shore + k`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::embedding::topologies::chimera_graph;
+///
+/// // A single D-Wave 2000Q-like unit cell
+/// let unit_cell = chimera_graph(1, 1, 4);
+///
+/// assert_eq!(8, unit_cell.num_qubits);
+/// assert_eq!(16, unit_cell.couplers.len());
+/// ```
+pub fn chimera_graph(rows: usize, columns: usize, shore: usize) -> HardwareGraph {
+    let label = |i: usize, j: usize, u: usize, k: usize| -> Qubit {
+        ((i * columns + j) * 2 + u) * shore + k
+    };
+    let mut couplers: Vec<Coupler> = vec![];
+
+    for i in 0..rows {
+        for j in 0..columns {
+            for k in 0..shore {
+                for kk in 0..shore {
+                    couplers.push((label(i, j, 0, k), label(i, j, 1, kk)));
+                }
+                if i + 1 < rows {
+                    couplers.push((label(i, j, 0, k), label(i + 1, j, 0, k)));
+                }
+                if j + 1 < columns {
+                    couplers.push((label(i, j, 1, k), label(i, j + 1, 1, k)));
+                }
+            }
+        }
+    }
+
+    HardwareGraph::new(rows * columns * 2 * shore, couplers)
+}
+
+const PEGASUS_VERTICAL_OFFSETS: [usize; 12] = [2, 2, 2, 2, 10, 10, 10, 10, 6, 6, 6, 6];
+const PEGASUS_HORIZONTAL_OFFSETS: [usize; 12] = [6, 6, 6, 6, 2, 2, 2, 2, 10, 10, 10, 10];
+
+/// Builds a Pegasus-like hardware graph of size `m`, using the standard qubit offsets. Every qubit has coordinates
+/// `(u, w, k, z)`, with orientation `u` in `0..2`, perpendicular offset `w` in `0..m`, index `k` in `0..12` and
+/// parallel offset `z` in `0..m - 1`. Interior qubits have 12 internal couplers, 2 external couplers and 1 odd coupler.
+///
+/// The qubit `(u, w, k, z)` has the label `((u * m + w) * 12 + k) * (m - 1) + z`. Qubits that do not belong to the
+/// main fabric are kept, so that labels stay contiguous, but have no couplers at all.
+pub fn pegasus_graph(m: usize) -> HardwareGraph {
+    assert!(m >= 2, "A Pegasus graph needs m to be at least 2");
+    let label =
+        |u: usize, w: usize, k: usize, z: usize| -> Qubit { ((u * m + w) * 12 + k) * (m - 1) + z };
+    let mut couplers: Vec<Coupler> = vec![];
+
+    for u in 0..2 {
+        for w in 0..m {
+            for k in 0..12 {
+                for z in 0..(m - 1) {
+                    if z + 1 < m - 1 {
+                        couplers.push((label(u, w, k, z), label(u, w, k, z + 1)));
+                    }
+                    if k % 2 == 0 {
+                        couplers.push((label(u, w, k, z), label(u, w, k + 1, z)));
+                    }
+                }
+            }
+        }
+    }
+
+    for w in 0..m {
+        for (k, vertical_offset) in PEGASUS_VERTICAL_OFFSETS.iter().enumerate() {
+            for z in 0..(m - 1) {
+                for (kk, horizontal_offset) in PEGASUS_HORIZONTAL_OFFSETS.iter().enumerate() {
+                    let other_w = z + usize::from(kk < *vertical_offset);
+                    let shift = usize::from(k < *horizontal_offset);
+                    if other_w >= m || w < shift || w - shift >= m - 1 {
+                        continue;
+                    }
+                    couplers.push((label(0, w, k, z), label(1, other_w, kk, w - shift)));
+                }
+            }
+        }
+    }
+
+    // Only the main fabric is kept, the few qubits at the corners that aren't connected to it are left isolated
+    let hardware_graph = HardwareGraph::new(24 * m * (m - 1), couplers);
+    let adjacency = hardware_graph.adjacency();
+    let mut component = vec![usize::MAX; hardware_graph.num_qubits];
+    let mut component_sizes = vec![];
+    for root in 0..hardware_graph.num_qubits {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        let mut size = 0;
+        let mut stack = vec![root];
+        component[root] = component_sizes.len();
+        while let Some(qubit) = stack.pop() {
+            size += 1;
+            for neighbour in &adjacency[qubit] {
+                if component[*neighbour] == usize::MAX {
+                    component[*neighbour] = component_sizes.len();
+                    stack.push(*neighbour);
+                }
+            }
+        }
+        component_sizes.push(size);
+    }
+    let fabric = (0..component_sizes.len())
+        .max_by_key(|component_index| component_sizes[*component_index])
+        .unwrap();
+
+    HardwareGraph::new(
+        hardware_graph.num_qubits,
+        hardware_graph
+            .couplers
+            .into_iter()
+            .filter(|(left_qubit, _)| component[*left_qubit] == fabric)
+            .collect(),
+    )
+}
+
+/// Builds a Zephyr-like hardware graph of size `m` and tile size `t`. Every qubit has coordinates `(u, w, k, j, z)`,
+/// with orientation `u` in `0..2`, perpendicular offset `w` in `0..2m + 1`, index `k` in `0..t`, `j` in `0..2` and
+/// parallel offset `z` in `0..m`. Interior qubits have `4t` internal couplers, 2 external couplers and 2 odd couplers.
+///
+/// The qubit `(u, w, k, j, z)` has the label `(((u * (2m + 1) + w) * t + k) * 2 + j) * m + z`.
+pub fn zephyr_graph(m: usize, t: usize) -> HardwareGraph {
+    let big_m = 2 * m + 1;
+    let label = |u: usize, w: usize, k: usize, j: usize, z: usize| -> Qubit {
+        (((u * big_m + w) * t + k) * 2 + j) * m + z
+    };
+    let mut couplers: Vec<Coupler> = vec![];
+
+    for u in 0..2 {
+        for w in 0..big_m {
+            for k in 0..t {
+                for j in 0..2 {
+                    for z in 0..m {
+                        if z + 1 < m {
+                            couplers.push((label(u, w, k, j, z), label(u, w, k, j, z + 1)));
+                        }
+                    }
+                }
+                for z in 0..m {
+                    couplers.push((label(u, w, k, 0, z), label(u, w, k, 1, z)));
+                    if z >= 1 {
+                        couplers.push((label(u, w, k, 0, z), label(u, w, k, 1, z - 1)));
+                    }
+                }
+            }
+        }
+    }
+
+    for w in 0..m {
+        for z in 0..m {
+            for k in 0..t {
+                for h in 0..t {
+                    for i in 0..2 {
+                        for j in 0..2 {
+                            for a in 0..2 {
+                                for b in 0..2 {
+                                    let vertical_w = 2 * w + 1 + a * (2 * i) - a;
+                                    let horizontal_w = 2 * z + 1 + b * (2 * j) - b;
+                                    couplers.push((
+                                        label(0, vertical_w, k, j, z),
+                                        label(1, horizontal_w, h, i, w),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    HardwareGraph::new(4 * t * m * big_m, couplers)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::embedding::topologies::{chimera_graph, pegasus_graph, zephyr_graph};
+    use crate::embedding::HardwareGraph;
+
+    fn degrees(hardware_graph: &HardwareGraph) -> Vec<usize> {
+        hardware_graph
+            .adjacency()
+            .iter()
+            .map(|neighbours| neighbours.len())
+            .collect()
+    }
+
+    fn is_connected(hardware_graph: &HardwareGraph) -> bool {
+        let adjacency = hardware_graph.adjacency();
+        let mut visited = vec![false; hardware_graph.num_qubits];
+        let root = (0..hardware_graph.num_qubits)
+            .find(|qubit| !adjacency[*qubit].is_empty())
+            .unwrap();
+        let mut stack = vec![root];
+        visited[root] = true;
+        while let Some(qubit) = stack.pop() {
+            for &neighbour in &adjacency[qubit] {
+                if !visited[neighbour] {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        visited
+            .iter()
+            .zip(adjacency.iter())
+            .all(|(visited, neighbours)| *visited || neighbours.is_empty())
+    }
+
+    #[test]
+    fn test_chimera_graph() {
+        let chimera = chimera_graph(16, 16, 4);
+
+        assert_eq!(2048, chimera.num_qubits);
+        assert_eq!(6016, chimera.couplers.len());
+        assert_eq!(Some(&6), degrees(&chimera).iter().max());
+        assert!(is_connected(&chimera));
+    }
+
+    #[test]
+    fn test_pegasus_graph() {
+        let pegasus = pegasus_graph(6);
+        let degrees = degrees(&pegasus);
+
+        assert_eq!(720, pegasus.num_qubits);
+        assert_eq!(680, degrees.iter().filter(|degree| **degree > 0).count());
+        assert_eq!(Some(&15), degrees.iter().max());
+        assert!(is_connected(&pegasus));
+    }
+
+    #[test]
+    fn test_zephyr_graph() {
+        let zephyr = zephyr_graph(3, 4);
+        let degrees = degrees(&zephyr);
+
+        assert_eq!(336, zephyr.num_qubits);
+        assert_eq!(Some(&20), degrees.iter().max());
+        assert!(is_connected(&zephyr));
+    }
+}
@@ -0,0 +1,174 @@
+//! Spin reversal, or gauge, transformations. A gauge is a choice of spins to flip: flipping spin `i` maps the state
+//! `s` to the one with `s_i` flipped, and the spin glass to the one with the signs of `h_i` and of every `J_ij` that
+//! involves `i` flipped, so that every state of the transformed spin glass has the same energy as the corresponding
+//! state of the original one. Samplers and annealers that favour some states over others, e.g. because of the sign of
+//! the fields, are biased differently under every gauge, so averaging their results over random gauges, with
+//! [sample_over_random_gauges], removes part of that bias.
+
+use crate::sample_set::SampleSet;
+use crate::types::{ExternalMagneticField, Interactions};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Returns a gauge of `num_spins` spins, where every spin is flipped with probability 1/2.
+pub fn random_gauge(num_spins: usize, seed: u64) -> Vec<bool> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..num_spins).map(|_| rng.gen::<bool>()).collect()
+}
+
+/// Returns the interaction terms and external magnetic field of the spin glass, given as the `interactions` and
+/// `external_magnetic_field` arguments, transformed by `gauge`, i.e. with every spin for which `gauge` is true flipped.
+/// Applying the same gauge twice gives back the original spin glass. Samples of the transformed spin glass are mapped
+/// back with [SampleSet::apply_gauge].
+///
+/// ### Example
+///
+/// ```
+/// use ernst::gauge::apply_gauge;
+/// use ernst::solvers::energy;
+///
+/// let interactions = vec![(0, 1, 1.0), (1, 2, -0.5)];
+/// let external_magnetic_field = vec![0.5, 0.0, -1.0];
+/// let gauge = vec![true, false, false];
+///
+/// let (gauged_interactions, gauged_external_magnetic_field) =
+///     apply_gauge(&interactions, &external_magnetic_field, &gauge);
+///
+/// assert_eq!(vec![(0, 1, -1.0), (1, 2, -0.5)], gauged_interactions);
+/// assert_eq!(vec![-0.5, 0.0, -1.0], gauged_external_magnetic_field);
+/// assert_eq!(
+///     energy(&interactions, &external_magnetic_field, &vec![true, true, false]),
+///     energy(&gauged_interactions, &gauged_external_magnetic_field, &vec![false, true, false]),
+/// );
+/// ```
+pub fn apply_gauge(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    gauge: &[bool],
+) -> (Interactions, ExternalMagneticField) {
+    assert_eq!(
+        external_magnetic_field.len(),
+        gauge.len(),
+        "The gauge needs a value for every spin"
+    );
+    let sign = |flip: bool| if flip { -1.0 } else { 1.0 };
+
+    (
+        interactions
+            .iter()
+            .map(|(i, j, interaction_strength)| {
+                (*i, *j, sign(gauge[*i] ^ gauge[*j]) * interaction_strength)
+            })
+            .collect(),
+        external_magnetic_field
+            .iter()
+            .zip(gauge)
+            .map(|(magnetic_field_strength, flip)| sign(*flip) * magnetic_field_strength)
+            .collect(),
+    )
+}
+
+/// Runs `sampler` on `num_gauges` random gauge transformations of the spin glass whose interaction terms and external
+/// magnetic field are given as the `interactions` and `external_magnetic_field` arguments, and returns all of its
+/// samples mapped back to the original spin glass. `sampler` is also passed the index of every gauge, so that it can
+/// derive its seed from it, and the gauges are derived from `seed`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::gauge::sample_over_random_gauges;
+/// use ernst::solvers::{energy, simulated_annealing, SimulatedAnnealingConfiguration};
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![1.0, 0.0];
+///
+/// let sample_set = sample_over_random_gauges(
+///     &copy_gate_interactions,
+///     &copy_gate_external_magnetic_field,
+///     5,
+///     42,
+///     |interactions, external_magnetic_field, gauge_index| {
+///         let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+///             num_reads: 10,
+///             seed: gauge_index as u64,
+///             ..Default::default()
+///         };
+///         simulated_annealing(interactions, external_magnetic_field, Some(&simulated_annealing_configuration))
+///     },
+/// );
+///
+/// assert_eq!(50, sample_set.len());
+/// // Every sample is mapped back to a state of the original spin glass with the same energy
+/// assert!(sample_set.iter().all(|sample| {
+///     sample.energy == energy(&copy_gate_interactions, &copy_gate_external_magnetic_field, &sample.state)
+/// }));
+/// assert_eq!(vec![true, true], sample_set.lowest().samples[0].state);
+/// ```
+pub fn sample_over_random_gauges(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    num_gauges: usize,
+    seed: u64,
+    mut sampler: impl FnMut(&Interactions, &ExternalMagneticField, usize) -> SampleSet,
+) -> SampleSet {
+    let mut sample_set = SampleSet::new();
+    for gauge_index in 0..num_gauges {
+        let gauge = random_gauge(
+            external_magnetic_field.len(),
+            seed.wrapping_add(gauge_index as u64),
+        );
+        let (gauged_interactions, gauged_external_magnetic_field) =
+            apply_gauge(interactions, external_magnetic_field, &gauge);
+        let gauged_sample_set = sampler(
+            &gauged_interactions,
+            &gauged_external_magnetic_field,
+            gauge_index,
+        )
+        .apply_gauge(&gauge);
+        sample_set.samples.extend(gauged_sample_set.samples);
+        sample_set.traces.extend(gauged_sample_set.traces);
+    }
+
+    sample_set
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gauge::{apply_gauge, random_gauge};
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::find_all_ground_states;
+
+    #[test]
+    fn test_gauges_preserve_the_spectrum() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(3, 3, CouplingDistribution::PlusMinusJ, 7);
+        let mut ground_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).samples;
+        ground_states.sort_by(|left, right| left.state.cmp(&right.state));
+
+        for seed in 0..5 {
+            let gauge = random_gauge(external_magnetic_field.len(), seed);
+            let (gauged_interactions, gauged_external_magnetic_field) =
+                apply_gauge(&interactions, &external_magnetic_field, &gauge);
+            let gauged_ground_states =
+                find_all_ground_states(&gauged_interactions, &gauged_external_magnetic_field);
+
+            let mut original_ground_states = gauged_ground_states.apply_gauge(&gauge).samples;
+            original_ground_states.sort_by(|left, right| left.state.cmp(&right.state));
+            assert_eq!(ground_states, original_ground_states);
+            assert_eq!(
+                (interactions.clone(), external_magnetic_field.clone()),
+                apply_gauge(
+                    &gauged_interactions,
+                    &gauged_external_magnetic_field,
+                    &gauge
+                )
+            );
+        }
+    }
+}
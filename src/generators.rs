@@ -0,0 +1,615 @@
+use crate::lattice::{standard_normal, CouplingDistribution, SquareLattice};
+use crate::nodelib::logic_gates::{NOT, OR};
+use crate::solvers::energy;
+use crate::spin_network::SpinNetwork;
+use crate::types::{Energy, ExternalMagneticField, Interactions, SpinIndex, State};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// A spin glass with a planted solution, i.e. one of its ground states is known by construction.
+/// - `interactions`: interaction terms of the spin glass
+/// - `external_magnetic_field`: external magnetic field of the spin glass
+/// - `ground_state`: the planted ground state
+/// - `ground_state_energy`: the energy of the planted ground state
+pub struct PlantedInstance {
+    pub interactions: Interactions,
+    pub external_magnetic_field: ExternalMagneticField,
+    pub ground_state: State,
+    pub ground_state_energy: Energy,
+}
+
+impl PlantedInstance {
+    fn new(
+        interactions: Interactions,
+        external_magnetic_field: ExternalMagneticField,
+        ground_state: State,
+    ) -> Self {
+        let ground_state_energy = energy(&interactions, &external_magnetic_field, &ground_state);
+
+        PlantedInstance {
+            interactions,
+            external_magnetic_field,
+            ground_state,
+            ground_state_energy,
+        }
+    }
+}
+
+/// Generates an Edwards-Anderson spin glass on a periodic `width` x `height` square lattice, with interaction strengths
+/// drawn from `coupling_distribution` and no external magnetic field. The spin at column `x` and row `y` has index
+/// `y * width + x`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::edwards_anderson_2d;
+/// use ernst::lattice::CouplingDistribution;
+///
+/// let (interactions, external_magnetic_field) =
+///     edwards_anderson_2d(4, 4, CouplingDistribution::PlusMinusJ, 42);
+///
+/// assert_eq!(16, external_magnetic_field.len());
+/// assert_eq!(32, interactions.len());
+/// ```
+pub fn edwards_anderson_2d(
+    width: usize,
+    height: usize,
+    coupling_distribution: CouplingDistribution,
+    seed: u64,
+) -> (Interactions, ExternalMagneticField) {
+    let spin_network = SquareLattice {
+        width,
+        height,
+        periodic: true,
+        coupling_distribution,
+        seed,
+    }
+    .build();
+
+    (
+        spin_network.interactions,
+        spin_network.external_magnetic_field,
+    )
+}
+
+/// Generates an Edwards-Anderson spin glass on a periodic `length` x `length` x `length` cubic lattice, with interaction
+/// strengths drawn from `coupling_distribution` and no external magnetic field. The spin at `(x, y, z)` has index
+/// `(z * length + y) * length + x`.
+pub fn edwards_anderson_3d(
+    length: usize,
+    coupling_distribution: CouplingDistribution,
+    seed: u64,
+) -> (Interactions, ExternalMagneticField) {
+    assert!(
+        length > 2,
+        "A periodic cubic lattice needs a length of at least 3"
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+    let spin_index = |x: usize, y: usize, z: usize| -> SpinIndex { (z * length + y) * length + x };
+    let mut interactions: Interactions = vec![];
+
+    for z in 0..length {
+        for y in 0..length {
+            for x in 0..length {
+                for neighbour in [
+                    spin_index((x + 1) % length, y, z),
+                    spin_index(x, (y + 1) % length, z),
+                    spin_index(x, y, (z + 1) % length),
+                ] {
+                    interactions.push((
+                        spin_index(x, y, z),
+                        neighbour,
+                        coupling_distribution.sample(&mut rng),
+                    ));
+                }
+            }
+        }
+    }
+
+    (interactions, vec![0.0; length * length * length])
+}
+
+/// Generates a Sherrington-Kirkpatrick spin glass of `n` fully connected spins, with interaction strengths drawn from
+/// `coupling_distribution` and scaled by 1/√n, and no external magnetic field.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::sherrington_kirkpatrick;
+/// use ernst::lattice::CouplingDistribution;
+///
+/// let (interactions, _) = sherrington_kirkpatrick(16, CouplingDistribution::PlusMinusJ, 42);
+///
+/// assert_eq!(16 * 15 / 2, interactions.len());
+/// assert!(interactions.iter().all(|(_, _, interaction_strength)| interaction_strength.abs() == 0.25));
+/// ```
+pub fn sherrington_kirkpatrick(
+    n: usize,
+    coupling_distribution: CouplingDistribution,
+    seed: u64,
+) -> (Interactions, ExternalMagneticField) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let scale = 1.0 / (n as f32).sqrt();
+    let mut interactions: Interactions = vec![];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            interactions.push((i, j, coupling_distribution.sample(&mut rng) * scale));
+        }
+    }
+
+    (interactions, vec![0.0; n])
+}
+
+/// Generates the max-cut problem of a random `degree`-regular graph with `n` vertices. Every edge becomes an
+/// antiferromagnetic interaction of strength -1.0, so that a state of energy `E` cuts `(edges - E) / 2` edges.
+/// The graph is sampled by pairing stubs of the vertices at random, redrawing only the pairs that would make loops or
+/// multiple edges, so that high degrees don't take exponentially many attempts.
+///
+/// ### Panics
+///
+/// Panics if no pairing of the stubs is found after many attempts, which is unlikely unless `degree` is close to `n`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::random_regular_max_cut;
+///
+/// let (interactions, external_magnetic_field) = random_regular_max_cut(10, 3, 42);
+///
+/// assert_eq!(10, external_magnetic_field.len());
+/// assert_eq!(15, interactions.len());
+/// ```
+pub fn random_regular_max_cut(
+    n: usize,
+    degree: usize,
+    seed: u64,
+) -> (Interactions, ExternalMagneticField) {
+    assert!(
+        degree < n,
+        "The degree must be smaller than the number of vertices"
+    );
+    assert!((n * degree) % 2 == 0, "n * degree must be even");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..MAX_PAIRING_ATTEMPTS {
+        if let Some(edges) = pair_stubs(n, degree, &mut rng) {
+            return (
+                edges.into_iter().map(|(i, j)| (i, j, -1.0)).collect(),
+                vec![0.0; n],
+            );
+        }
+    }
+    panic!(
+        "Couldn't sample a {degree}-regular graph with {n} vertices in {MAX_PAIRING_ATTEMPTS} attempts"
+    );
+}
+
+/// How many times [random_regular_max_cut] starts the pairing of stubs over before it gives up.
+const MAX_PAIRING_ATTEMPTS: usize = 1000;
+
+/// Pairs `degree` stubs of each of `n` vertices into the edges of a simple graph, sorted, with the algorithm of Steger
+/// and Wormald: random pairs of stubs are joined as long as they'd make neither a loop nor a multiple edge, and only
+/// the failed pair is drawn again. Returns None if the stubs that are left can't be paired, which is rare.
+fn pair_stubs(n: usize, degree: usize, rng: &mut StdRng) -> Option<Vec<(SpinIndex, SpinIndex)>> {
+    let mut stubs: Vec<SpinIndex> = (0..n)
+        .flat_map(|vertex| std::iter::repeat(vertex).take(degree))
+        .collect();
+    let mut edges = HashSet::new();
+    let is_suitable = |edges: &HashSet<(SpinIndex, SpinIndex)>, i: SpinIndex, j: SpinIndex| {
+        i != j && !edges.contains(&(i.min(j), i.max(j)))
+    };
+
+    while !stubs.is_empty() {
+        let first = rng.gen_range(0..stubs.len());
+        let second = rng.gen_range(0..stubs.len());
+        if first != second && is_suitable(&edges, stubs[first], stubs[second]) {
+            let (i, j) = (stubs[first], stubs[second]);
+            edges.insert((i.min(j), i.max(j)));
+            stubs.swap_remove(first.max(second));
+            stubs.swap_remove(first.min(second));
+            continue;
+        }
+        // Draws only fail often near the end, when it's worth checking whether any pair is left at all
+        let mut vertices = stubs.clone();
+        vertices.sort_unstable();
+        vertices.dedup();
+        let has_suitable_pair = vertices.iter().enumerate().any(|(position, i)| {
+            vertices[position + 1..]
+                .iter()
+                .any(|j| is_suitable(&edges, *i, *j))
+        });
+        if !has_suitable_pair {
+            return None;
+        }
+    }
+    let mut edges: Vec<_> = edges.into_iter().collect();
+    edges.sort_unstable();
+
+    Some(edges)
+}
+
+/// Generates a frustrated loops instance on a periodic `width` x `height` square lattice, with a planted ground state.
+/// `num_loops` loops are found by random walks on the lattice; on each loop all couplings but one, chosen at random,
+/// are satisfied by the planted state. Since the planted state minimizes the energy of every single loop, it's a
+/// ground state of their sum.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::frustrated_loops;
+///
+/// let (interactions, external_magnetic_field) = frustrated_loops(6, 6, 12, 42);
+///
+/// assert_eq!(36, external_magnetic_field.len());
+/// assert!(interactions.len() <= 72);
+/// ```
+pub fn frustrated_loops(
+    width: usize,
+    height: usize,
+    num_loops: usize,
+    seed: u64,
+) -> (Interactions, ExternalMagneticField) {
+    let planted_instance = planted_frustrated_loops(width, height, num_loops, seed);
+
+    (
+        planted_instance.interactions,
+        planted_instance.external_magnetic_field,
+    )
+}
+
+/// Same as [frustrated_loops], but it also returns the planted ground state and its energy.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::planted_frustrated_loops;
+/// use ernst::solvers::find_all_ground_states;
+///
+/// let planted_instance = planted_frustrated_loops(4, 4, 6, 42);
+/// let ground_states = find_all_ground_states(
+///     &planted_instance.interactions,
+///     &planted_instance.external_magnetic_field,
+/// );
+///
+/// assert!(ground_states.iter().any(|sample| sample.state == planted_instance.ground_state));
+/// ```
+pub fn planted_frustrated_loops(
+    width: usize,
+    height: usize,
+    num_loops: usize,
+    seed: u64,
+) -> PlantedInstance {
+    let lattice = SquareLattice {
+        width,
+        height,
+        periodic: true,
+        ..Default::default()
+    };
+    assert!(
+        width > 2 && height > 2,
+        "A periodic square lattice needs a width and a height of at least 3"
+    );
+    let n = width * height;
+    let spin_index = |(x, y): (usize, usize)| -> SpinIndex { y * width + x };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let planted_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let spin_value = |spin_index: SpinIndex| if planted_state[spin_index] { 1.0 } else { -1.0 };
+
+    let mut couplings: Vec<((SpinIndex, SpinIndex), f32)> = vec![];
+    for _ in 0..num_loops {
+        // Random walk that never steps back, until it crosses itself
+        let mut walk = vec![(rng.gen_range(0..width), rng.gen_range(0..height))];
+        let loop_start = loop {
+            let current = *walk.last().unwrap();
+            let previous = walk.len().checked_sub(2).map(|index| walk[index]);
+            let next = *lattice
+                .neighbours(current)
+                .iter()
+                .filter(|neighbour| Some(**neighbour) != previous)
+                .copied()
+                .collect::<Vec<_>>()
+                .choose(&mut rng)
+                .unwrap();
+            if let Some(loop_start) = walk.iter().position(|position| *position == next) {
+                walk.push(next);
+                break loop_start;
+            }
+            walk.push(next);
+        };
+
+        let cycle: Vec<SpinIndex> = walk[loop_start..].iter().copied().map(spin_index).collect();
+        let frustrated_edge = rng.gen_range(0..(cycle.len() - 1));
+        for (edge, pair) in cycle.windows(2).enumerate() {
+            let (i, j) = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+            let sign = if edge == frustrated_edge { -1.0 } else { 1.0 };
+            couplings.push(((i, j), sign * spin_value(i) * spin_value(j)));
+        }
+    }
+
+    let mut summed_couplings: Vec<((SpinIndex, SpinIndex), f32)> = vec![];
+    couplings.sort_by_key(|(pair, _)| *pair);
+    for (pair, interaction_strength) in couplings {
+        match summed_couplings.last_mut() {
+            Some((last_pair, last_strength)) if *last_pair == pair => {
+                *last_strength += interaction_strength
+            }
+            _ => summed_couplings.push((pair, interaction_strength)),
+        }
+    }
+    let interactions = summed_couplings
+        .into_iter()
+        .filter(|(_, interaction_strength)| *interaction_strength != 0.0)
+        .map(|((i, j), interaction_strength)| (i, j, interaction_strength))
+        .collect();
+
+    PlantedInstance::new(interactions, vec![0.0; n], planted_state)
+}
+
+/// Generates a Wishart planted ensemble instance of `n` fully connected spins. The interaction matrix is
+/// -W W<sup>T</sup> / n, where the `m` columns of W are Gaussian vectors orthogonal to the planted state. The ratio
+/// `m / n` tunes the hardness of the instance.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::wishart;
+///
+/// let (interactions, external_magnetic_field) = wishart(8, 4, 42);
+///
+/// assert_eq!(8, external_magnetic_field.len());
+/// assert_eq!(8 * 7 / 2, interactions.len());
+/// ```
+pub fn wishart(n: usize, m: usize, seed: u64) -> (Interactions, ExternalMagneticField) {
+    let planted_instance = planted_wishart(n, m, seed);
+
+    (
+        planted_instance.interactions,
+        planted_instance.external_magnetic_field,
+    )
+}
+
+/// Same as [wishart], but it also returns the planted ground state and its energy.
+pub fn planted_wishart(n: usize, m: usize, seed: u64) -> PlantedInstance {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let planted_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let planted_values: Vec<f32> = planted_state
+        .iter()
+        .map(|spin| if *spin { 1.0 } else { -1.0 })
+        .collect();
+
+    let columns: Vec<Vec<f32>> = (0..m)
+        .map(|_| {
+            let column: Vec<f32> = (0..n).map(|_| standard_normal(&mut rng)).collect();
+            let projection: f32 = column
+                .iter()
+                .zip(planted_values.iter())
+                .map(|(w, t)| w * t)
+                .sum::<f32>()
+                / n as f32;
+
+            column
+                .iter()
+                .zip(planted_values.iter())
+                .map(|(w, t)| w - projection * t)
+                .collect()
+        })
+        .collect();
+
+    let mut interactions: Interactions = vec![];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let correlation: f32 = columns.iter().map(|column| column[i] * column[j]).sum();
+            interactions.push((i, j, -correlation / n as f32));
+        }
+    }
+
+    PlantedInstance::new(interactions, vec![0.0; n], planted_state)
+}
+
+/// Generates a tile planting instance on a periodic `width` x `height` square lattice, where both `width` and `height`
+/// are even. The plaquettes of one colour of a checkerboard cover every coupling of the lattice exactly once; each of
+/// them becomes a tile whose couplings are all satisfied by the planted state, except for one of them in a
+/// `frustrated_fraction` of the tiles. The planted state minimizes the energy of every single tile, so it's a ground
+/// state of the whole lattice.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::tile_planting;
+///
+/// let planted_instance = tile_planting(4, 4, 0.5, 42);
+///
+/// assert_eq!(32, planted_instance.interactions.len());
+/// assert!(planted_instance.ground_state_energy >= -32.0);
+/// ```
+pub fn tile_planting(
+    width: usize,
+    height: usize,
+    frustrated_fraction: f32,
+    seed: u64,
+) -> PlantedInstance {
+    assert!(
+        width > 2 && height > 2 && width % 2 == 0 && height % 2 == 0,
+        "Tile planting needs an even width and an even height of at least 4"
+    );
+    let n = width * height;
+    let spin_index = |x: usize, y: usize| -> SpinIndex { (y % height) * width + (x % width) };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let planted_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let spin_value = |spin_index: SpinIndex| if planted_state[spin_index] { 1.0 } else { -1.0 };
+
+    let mut interactions: Interactions = vec![];
+    for y in 0..height {
+        for x in ((y % 2)..width).step_by(2) {
+            let corners = [
+                spin_index(x, y),
+                spin_index(x + 1, y),
+                spin_index(x + 1, y + 1),
+                spin_index(x, y + 1),
+            ];
+            let frustrated_edge = if rng.gen::<f32>() < frustrated_fraction {
+                Some(rng.gen_range(0..4))
+            } else {
+                None
+            };
+            for edge in 0..4 {
+                let (i, j) = (corners[edge], corners[(edge + 1) % 4]);
+                let sign = if Some(edge) == frustrated_edge {
+                    -1.0
+                } else {
+                    1.0
+                };
+                interactions.push((i.min(j), i.max(j), sign * spin_value(i) * spin_value(j)));
+            }
+        }
+    }
+
+    PlantedInstance::new(interactions, vec![0.0; n], planted_state)
+}
+
+/// Generates a random 3-SAT instance with `num_variables` variables and `num_clauses` clauses, built as a circuit of
+/// [NOT] and [OR] gates. The variables are the spins `0..num_variables`, and the output of every clause is clamped to
+/// true by an external magnetic field of strength `clamping_strength`, so that satisfying assignments are ground
+/// states whenever the instance is satisfiable.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::generators::random_3_sat;
+///
+/// let (interactions, external_magnetic_field) = random_3_sat(5, 3, 1.0, 42);
+///
+/// assert!(external_magnetic_field.len() > 5);
+/// assert!(interactions.iter().all(|(i, j, _)| i != j));
+/// ```
+pub fn random_3_sat(
+    num_variables: usize,
+    num_clauses: usize,
+    clamping_strength: f32,
+    seed: u64,
+) -> (Interactions, ExternalMagneticField) {
+    assert!(
+        num_variables >= 3,
+        "A 3-SAT clause needs at least 3 variables"
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut spin_network = SpinNetwork::new();
+    let variables: Vec<SpinIndex> = (0..num_variables)
+        .map(|_| spin_network.add_input_node(0.0))
+        .collect();
+
+    for _ in 0..num_clauses {
+        let literals: Vec<SpinIndex> = variables
+            .choose_multiple(&mut rng, 3)
+            .map(|variable| {
+                if rng.gen::<bool>() {
+                    spin_network.add_unary_node(*variable, &NOT::default())
+                } else {
+                    *variable
+                }
+            })
+            .collect();
+        let partial_clause = spin_network.add_binary_node(literals[0], literals[1], &OR::default());
+        let clause = spin_network.add_binary_node(partial_clause, literals[2], &OR::default());
+        spin_network.external_magnetic_field[clause] += clamping_strength;
+    }
+
+    (
+        spin_network.interactions,
+        spin_network.external_magnetic_field,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::{
+        edwards_anderson_3d, planted_frustrated_loops, planted_wishart, random_3_sat,
+        random_regular_max_cut, tile_planting, PlantedInstance,
+    };
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::{energy, find_all_ground_states};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_edwards_anderson_3d() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_3d(3, CouplingDistribution::Gaussian, 42);
+
+        assert_eq!(27, external_magnetic_field.len());
+        assert_eq!(81, interactions.len());
+        assert_eq!(
+            interactions,
+            edwards_anderson_3d(3, CouplingDistribution::Gaussian, 42).0
+        );
+    }
+
+    #[test]
+    fn test_random_regular_max_cut() {
+        for (n, degree) in [(20, 3), (100, 8), (100, 20), (10, 9)] {
+            let (interactions, _) = random_regular_max_cut(n, degree, 7);
+            let mut degrees = vec![0; n];
+            for (i, j, _) in &interactions {
+                degrees[*i] += 1;
+                degrees[*j] += 1;
+            }
+            let edges: HashSet<_> = interactions.iter().map(|(i, j, _)| (*i, *j)).collect();
+
+            assert!(degrees.iter().all(|vertex_degree| *vertex_degree == degree));
+            assert_eq!(interactions.len(), edges.len());
+            assert!(interactions.iter().all(|(i, j, _)| i < j));
+        }
+    }
+
+    fn assert_is_ground_state(planted_instance: &PlantedInstance) {
+        let ground_states = find_all_ground_states(
+            &planted_instance.interactions,
+            &planted_instance.external_magnetic_field,
+        );
+
+        assert!(
+            (ground_states.lowest_energy().unwrap() - planted_instance.ground_state_energy).abs()
+                < 1e-4
+        );
+        assert_eq!(
+            planted_instance.ground_state_energy,
+            energy(
+                &planted_instance.interactions,
+                &planted_instance.external_magnetic_field,
+                &planted_instance.ground_state
+            )
+        );
+    }
+
+    #[test]
+    fn test_planted_frustrated_loops() {
+        assert_is_ground_state(&planted_frustrated_loops(4, 4, 6, 42));
+    }
+
+    #[test]
+    fn test_planted_wishart() {
+        assert_is_ground_state(&planted_wishart(12, 6, 42));
+    }
+
+    #[test]
+    fn test_tile_planting() {
+        let planted_instance = tile_planting(4, 4, 0.5, 42);
+
+        assert_eq!(32, planted_instance.interactions.len());
+        assert_is_ground_state(&planted_instance);
+    }
+
+    #[test]
+    fn test_random_3_sat() {
+        // A single clause is violated by exactly one of the 8 assignments of its variables
+        let (interactions, external_magnetic_field) = random_3_sat(3, 1, 1.0, 42);
+        let satisfying_assignments: HashSet<_> =
+            find_all_ground_states(&interactions, &external_magnetic_field)
+                .into_iter()
+                .map(|sample| sample.state[0..3].to_vec())
+                .collect();
+
+        assert_eq!(7, satisfying_assignments.len());
+    }
+}
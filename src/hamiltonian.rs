@@ -1,4 +1,4 @@
-use crate::types::{CompactState, Energy, ExternalMagneticField, Interactions, LinearizedUpperTriangularMatrix, SpinIndex, State};
+use crate::types::{CompactState, Energy, ExternalMagneticField, Interactions, InteractionStrength, LinearizedUpperTriangularMatrix, SpinIndex, State};
 use ftree::FenwickTree;
 
 pub(crate) struct TwoLocalHamiltonian {
@@ -7,6 +7,7 @@ pub(crate) struct TwoLocalHamiltonian {
     external_magnetic_field: ExternalMagneticField,
     interaction_energy: FenwickTree<Energy>,
     magnetic_field_energy: FenwickTree<Energy>,
+    delta_energies: Vec<f64>,
 }
 
 impl TwoLocalHamiltonian {
@@ -19,13 +20,11 @@ impl TwoLocalHamiltonian {
         external_magnetic_field: ExternalMagneticField,
         initial_state: Option<State>,
     ) -> Self {
-        let n = interactions
-            .iter()
-            .flat_map(|(i, j, _)| std::iter::once(i).chain(std::iter::once(j)))
-            .max()
-            .unwrap()
-            + 1;
-        assert_eq!(external_magnetic_field.len(), n);
+        let n = external_magnetic_field.len();
+        assert!(
+            interactions.iter().all(|(i, j, _)| *i < n && *j < n),
+            "The interaction vector refers to spins that have no external magnetic field"
+        );
 
         let mut spins = CompactState::with_capacity(n);
         if let Some(initial_spins) = initial_state {
@@ -44,16 +43,19 @@ impl TwoLocalHamiltonian {
         let magnetic_field_strength_values: Vec<Energy> = (0..n)
             .map(|i| {
                 if spins.contains(i) {
-                    external_magnetic_field[i].into()
+                    external_magnetic_field[i]
                 } else {
-                    (-external_magnetic_field[i]).into()
+                    -external_magnetic_field[i]
                 }
             })
             .collect();
         let magnetic_field_energy = FenwickTree::from_iter(magnetic_field_strength_values);
 
-        let total_interactions = n * (n - 1) / 2;
+        let total_interactions = n * n.saturating_sub(1) / 2;
         let mut linearized_interactions = vec![0.0; total_interactions];
+        let mut interaction_energy_values = vec![0.0; total_interactions];
+        let mut local_fields: Vec<f64> =
+            external_magnetic_field.iter().map(|h| *h as f64).collect();
 
         for (i, j, interaction_strength) in interactions.iter() {
             let smaller = std::cmp::min(i, j);
@@ -61,9 +63,21 @@ impl TwoLocalHamiltonian {
             let index = TwoLocalHamiltonian::map_interaction_to_index(*smaller, *greater, n);
             let i_spin_value = if spins.contains(*i) { 1.0 } else { -1.0 };
             let j_spin_value = if spins.contains(*j) { 1.0 } else { -1.0 };
-            linearized_interactions[index] = interaction_strength * i_spin_value * j_spin_value;
+            // Repeated pairs of spins add up, like they do in the energy of a state and in SparseCouplings
+            linearized_interactions[index] += *interaction_strength;
+            interaction_energy_values[index] += interaction_strength * i_spin_value * j_spin_value;
+            local_fields[*i] += (interaction_strength * j_spin_value) as f64;
+            local_fields[*j] += (interaction_strength * i_spin_value) as f64;
         }
-        let interaction_energy = FenwickTree::from_iter(linearized_interactions.clone());
+        let interaction_energy = FenwickTree::from_iter(interaction_energy_values);
+        let delta_energies = local_fields
+            .iter()
+            .enumerate()
+            .map(|(i, local_field)| {
+                let spin_value = if spins.contains(i) { 1.0 } else { -1.0 };
+                2.0 * spin_value * local_field
+            })
+            .collect();
 
         TwoLocalHamiltonian {
             spins,
@@ -71,6 +85,7 @@ impl TwoLocalHamiltonian {
             external_magnetic_field,
             interaction_energy,
             magnetic_field_energy,
+            delta_energies,
         }
     }
 
@@ -92,9 +107,12 @@ impl TwoLocalHamiltonian {
                     let other_spin_sign = if self.spins.contains(j) { 1.0 } else { -1.0 };
                     self.interaction_energy
                         .add_at(index, interaction_strength * sign_change * other_spin_sign);
+                    self.delta_energies[j] +=
+                        2.0 * (interaction_strength * sign_change * other_spin_sign) as f64;
                 }
             }
         }
+        self.delta_energies[spin] = -self.delta_energies[spin];
 
         if let Some(magnetic_field_strength) = self.external_magnetic_field.get(spin) {
             self.magnetic_field_energy
@@ -110,13 +128,144 @@ impl TwoLocalHamiltonian {
             .magnetic_field_energy
             .prefix_sum(self.magnetic_field_energy.len(), 0.0);
 
-        return -interaction_energy + -external_magnetic_field_energy;
+        -interaction_energy + -external_magnetic_field_energy
+    }
+
+    /// Returns by how much the energy would change if `spin` was flipped. The energy change of every spin is kept up
+    /// to date as spins are flipped, so that it's known in constant time.
+    pub fn delta_energy(&self, spin: SpinIndex) -> Energy {
+        self.delta_energies[spin] as Energy
+    }
+}
+
+/// Sparse and immutable couplings of a two-local hamiltonian, meant to be shared by many [SpinChain]s. Repeated
+/// interactions between the same pair of spins add up, like they do in [crate::solvers::energy] and in
+/// [TwoLocalHamiltonian].
+pub(crate) struct SparseCouplings {
+    pub(crate) neighbours: Vec<Vec<(SpinIndex, InteractionStrength)>>,
+    pub(crate) external_magnetic_field: ExternalMagneticField,
+}
+
+impl SparseCouplings {
+    pub fn new(
+        interactions: &Interactions,
+        external_magnetic_field: &ExternalMagneticField,
+    ) -> Self {
+        let n = external_magnetic_field.len();
+        let mut neighbours: Vec<Vec<(SpinIndex, InteractionStrength)>> = vec![vec![]; n];
+        for (i, j, interaction_strength) in interactions {
+            assert!(
+                *i < n && *j < n,
+                "The interaction vector refers to spins that have no external magnetic field"
+            );
+            if i == j {
+                continue;
+            }
+            for (from, to) in [(*i, *j), (*j, *i)] {
+                match neighbours[from]
+                    .iter_mut()
+                    .find(|(neighbour, _)| *neighbour == to)
+                {
+                    Some((_, summed_strength)) => *summed_strength += interaction_strength,
+                    None => neighbours[from].push((to, *interaction_strength)),
+                }
+            }
+        }
+
+        SparseCouplings {
+            neighbours,
+            external_magnetic_field: external_magnetic_field.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.external_magnetic_field.len()
+    }
+
+    pub fn energy(&self, spins: &State) -> Energy {
+        let spin_value = |spin_index: SpinIndex| if spins[spin_index] { 1.0 } else { -1.0 };
+        let mut energy = 0.0;
+        for (i, neighbours) in self.neighbours.iter().enumerate() {
+            energy -= self.external_magnetic_field[i] as f64 * spin_value(i);
+            for (j, interaction_strength) in neighbours {
+                if i < *j {
+                    energy -= *interaction_strength as f64 * spin_value(i) * spin_value(*j);
+                }
+            }
+        }
+
+        energy as Energy
+    }
+}
+
+/// The state of a single Markov chain over [SparseCouplings]. It keeps track of the local field of every spin, so
+/// that the energy change of a flip is known in constant time and a flip costs as much as the degree of the spin.
+#[derive(Clone)]
+pub(crate) struct SpinChain<'a> {
+    couplings: &'a SparseCouplings,
+    pub(crate) spins: State,
+    local_fields: Vec<f64>,
+    energy: f64,
+}
+
+impl<'a> SpinChain<'a> {
+    pub fn new(couplings: &'a SparseCouplings, initial_state: State) -> Self {
+        assert_eq!(
+            initial_state.len(),
+            couplings.len(),
+            "The initial state has a different number of spins than the couplings"
+        );
+        let spin_value = |spin: bool| if spin { 1.0 } else { -1.0 };
+        let local_fields = (0..couplings.len())
+            .map(|i| {
+                couplings.external_magnetic_field[i] as f64
+                    + couplings.neighbours[i]
+                        .iter()
+                        .map(|(j, interaction_strength)| {
+                            *interaction_strength as f64 * spin_value(initial_state[*j])
+                        })
+                        .sum::<f64>()
+            })
+            .collect();
+        let energy = couplings.energy(&initial_state) as f64;
+
+        SpinChain {
+            couplings,
+            spins: initial_state,
+            local_fields,
+            energy,
+        }
+    }
+
+    /// Returns by how much the energy would change if `spin` was flipped.
+    pub fn delta_energy(&self, spin: SpinIndex) -> Energy {
+        let spin_value = if self.spins[spin] { 1.0 } else { -1.0 };
+
+        (2.0 * spin_value * self.local_fields[spin]) as Energy
+    }
+
+    pub fn flip_spin(&mut self, spin: SpinIndex) {
+        let spin_value = if self.spins[spin] { 1.0 } else { -1.0 };
+        self.energy += 2.0 * spin_value * self.local_fields[spin];
+        self.spins[spin] = !self.spins[spin];
+        for (j, interaction_strength) in &self.couplings.neighbours[spin] {
+            self.local_fields[*j] -= 2.0 * spin_value * *interaction_strength as f64;
+        }
+    }
+
+    pub fn current_energy(&self) -> Energy {
+        self.energy as Energy
+    }
+
+    pub fn couplings(&self) -> &'a SparseCouplings {
+        self.couplings
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::hamiltonian::TwoLocalHamiltonian;
+    use crate::hamiltonian::{SparseCouplings, SpinChain, TwoLocalHamiltonian};
+    use crate::solvers::energy;
     use crate::types::{ExternalMagneticField, Interactions};
 
     #[test]
@@ -137,4 +286,84 @@ mod tests {
         hamiltonian.flip_spin(2);
         assert_eq!(2.0, hamiltonian.current_energy());
     }
+
+    #[test]
+    fn test_total_energy_from_initial_state() {
+        let interactions: Interactions = vec![(0, 1, -1.0), (1, 2, 2.0), (0, 2, 2.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![-1.0, -1.0, -3.0];
+
+        let mut hamiltonian = TwoLocalHamiltonian::new(
+            interactions,
+            external_magnetic_field,
+            Some(vec![true, true, false]),
+        );
+
+        assert_eq!(4.0, hamiltonian.current_energy());
+
+        hamiltonian.flip_spin(2);
+        assert_eq!(2.0, hamiltonian.current_energy());
+
+        hamiltonian.flip_spin(0);
+        assert_eq!(2.0, hamiltonian.current_energy());
+
+        hamiltonian.flip_spin(1);
+        assert_eq!(6.0, hamiltonian.current_energy());
+    }
+
+    #[test]
+    fn test_total_energy_without_spins() {
+        let hamiltonian = TwoLocalHamiltonian::new(vec![], vec![], None);
+
+        assert_eq!(0.0, hamiltonian.current_energy());
+    }
+
+    #[test]
+    fn test_repeated_interactions_add_up() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 0, 1.0), (1, 2, -1.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.5, 0.0, 0.0];
+        let couplings = SparseCouplings::new(&interactions, &external_magnetic_field);
+
+        let mut hamiltonian = TwoLocalHamiltonian::new(
+            interactions.clone(),
+            external_magnetic_field.clone(),
+            Some(vec![true, true, true]),
+        );
+        let mut spin_chain = SpinChain::new(&couplings, vec![true, true, true]);
+
+        for spin in [0, 2, 1, 0] {
+            assert_eq!(
+                energy(&interactions, &external_magnetic_field, &spin_chain.spins),
+                hamiltonian.current_energy()
+            );
+            assert_eq!(hamiltonian.current_energy(), spin_chain.current_energy());
+            for other_spin in 0..3 {
+                assert_eq!(
+                    hamiltonian.delta_energy(other_spin),
+                    spin_chain.delta_energy(other_spin)
+                );
+            }
+            hamiltonian.flip_spin(spin);
+            spin_chain.flip_spin(spin);
+        }
+    }
+
+    #[test]
+    fn test_spin_chain() {
+        let interactions: Interactions = vec![(0, 1, -1.0), (1, 2, 2.0), (0, 2, 1.0), (0, 2, 1.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![-1.0, -1.0, -3.0];
+        let couplings = SparseCouplings::new(&interactions, &external_magnetic_field);
+
+        let mut spin_chain = SpinChain::new(&couplings, vec![true, true, false]);
+        assert_eq!(4.0, spin_chain.current_energy());
+
+        for (spin, expected_energy) in [(2, 2.0), (0, 2.0), (1, 6.0)] {
+            let energy_before_flip = spin_chain.current_energy();
+            let delta_energy = spin_chain.delta_energy(spin);
+            spin_chain.flip_spin(spin);
+
+            assert_eq!(expected_energy, spin_chain.current_energy());
+            assert_eq!(expected_energy, couplings.energy(&spin_chain.spins));
+            assert_eq!(expected_energy - energy_before_flip, delta_energy);
+        }
+    }
 }
@@ -0,0 +1,260 @@
+use crate::spin_network::SpinNetwork;
+use crate::types::{GridPosition, InteractionStrength};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The distribution that random interaction strengths are drawn from.
+/// - `PlusMinusJ`: either 1.0 or -1.0, with equal probability
+/// - `Gaussian`: normally distributed, with zero mean and unit variance
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CouplingDistribution {
+    PlusMinusJ,
+    Gaussian,
+}
+
+impl CouplingDistribution {
+    pub(crate) fn sample(&self, rng: &mut StdRng) -> InteractionStrength {
+        match self {
+            CouplingDistribution::PlusMinusJ => {
+                if rng.gen::<bool>() {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            CouplingDistribution::Gaussian => standard_normal(rng),
+        }
+    }
+}
+
+/// Draws from the standard normal distribution with the Box-Muller transform.
+pub(crate) fn standard_normal(rng: &mut StdRng) -> f32 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen::<f64>();
+
+    ((-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()) as f32
+}
+
+/// A SquareLattice describes a `width` x `height` 2D grid of spins, with either open or `periodic` boundaries.
+/// Its [SquareLattice::build] method creates the corresponding nearest-neighbour spin glass, with interaction strengths
+/// drawn from `coupling_distribution`.
+/// - `width`: number of columns
+/// - `height`: number of rows
+/// - `periodic`: if true, then the last column is coupled to the first one, and the last row to the first one
+/// - `coupling_distribution`: distribution of the interaction strengths
+/// - `seed`: rng seed that ensures the whole process to be repeatable
+pub struct SquareLattice {
+    pub width: usize,
+    pub height: usize,
+    pub periodic: bool,
+    pub coupling_distribution: CouplingDistribution,
+    pub seed: u64,
+}
+
+impl Default for SquareLattice {
+    fn default() -> Self {
+        SquareLattice {
+            width: 8,
+            height: 8,
+            periodic: false,
+            coupling_distribution: CouplingDistribution::PlusMinusJ,
+            seed: 42,
+        }
+    }
+}
+
+impl SquareLattice {
+    /// Creates a new open `width` x `height` SquareLattice with ±J interactions.
+    pub fn new(width: usize, height: usize) -> Self {
+        SquareLattice {
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+    /// Returns whether `position` lies within the lattice.
+    pub fn contains(&self, position: GridPosition) -> bool {
+        position.0 < self.width && position.1 < self.height
+    }
+    /// Returns the positions that are nearest neighbours of `position`, at most four.
+    pub fn neighbours(&self, position: GridPosition) -> Vec<GridPosition> {
+        let (x, y) = position;
+        let mut neighbours = vec![];
+        if x + 1 < self.width {
+            neighbours.push((x + 1, y));
+        } else if self.periodic && self.width > 2 {
+            neighbours.push((0, y));
+        }
+        if x > 0 {
+            neighbours.push((x - 1, y));
+        } else if self.periodic && self.width > 2 {
+            neighbours.push((self.width - 1, y));
+        }
+        if y + 1 < self.height {
+            neighbours.push((x, y + 1));
+        } else if self.periodic && self.height > 2 {
+            neighbours.push((x, 0));
+        }
+        if y > 0 {
+            neighbours.push((x, y - 1));
+        } else if self.periodic && self.height > 2 {
+            neighbours.push((x, self.height - 1));
+        }
+        neighbours.sort_unstable();
+        neighbours.dedup();
+
+        neighbours
+    }
+    /// Returns whether `left_position` and `right_position` are nearest neighbours.
+    pub fn are_neighbours(
+        &self,
+        left_position: GridPosition,
+        right_position: GridPosition,
+    ) -> bool {
+        self.neighbours(left_position).contains(&right_position)
+    }
+    /// Creates the spin glass that lives on the lattice. The spin at column `x` and row `y` has index
+    /// `y * width + x`, and is placed at `(x, y)`.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::lattice::{CouplingDistribution, SquareLattice};
+    ///
+    /// let lattice = SquareLattice {
+    ///     width: 4,
+    ///     height: 3,
+    ///     periodic: true,
+    ///     coupling_distribution: CouplingDistribution::Gaussian,
+    ///     seed: 7,
+    /// };
+    /// let spin_network = lattice.build();
+    ///
+    /// assert_eq!(12, spin_network.external_magnetic_field.len());
+    /// assert_eq!(24, spin_network.interactions.len());
+    /// assert!(spin_network.has_only_nearest_neighbour_couplings(&lattice));
+    /// ```
+    pub fn build(&self) -> SpinNetwork {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut spin_network = SpinNetwork::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let spin_index = spin_network.add_auxiliary_node(0.0);
+                spin_network.place_spin(spin_index, (x, y));
+            }
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let spin_index = y * self.width + x;
+                for (other_x, other_y) in self.neighbours((x, y)) {
+                    let other_spin_index = other_y * self.width + other_x;
+                    if spin_index < other_spin_index {
+                        spin_network.interactions.push((
+                            spin_index,
+                            other_spin_index,
+                            self.coupling_distribution.sample(&mut rng),
+                        ));
+                    }
+                }
+            }
+        }
+
+        spin_network
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lattice::{CouplingDistribution, SquareLattice};
+    use crate::nodelib::logic_gates::{AND, OR};
+    use crate::spin_network::SpinNetwork;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_square_lattice() {
+        let open = SquareLattice::new(3, 3).build();
+        let periodic = SquareLattice {
+            width: 3,
+            height: 3,
+            periodic: true,
+            ..Default::default()
+        }
+        .build();
+
+        assert_eq!(12, open.interactions.len());
+        assert_eq!(18, periodic.interactions.len());
+        assert!(open
+            .interactions
+            .iter()
+            .all(|(_, _, interaction_strength)| interaction_strength.abs() == 1.0));
+        assert_eq!(Some((2, 1)), open.position(5));
+    }
+
+    #[test]
+    fn test_gaussian_couplings_are_seeded() {
+        let lattice = SquareLattice {
+            coupling_distribution: CouplingDistribution::Gaussian,
+            ..Default::default()
+        };
+
+        assert_eq!(lattice.build().interactions, lattice.build().interactions);
+    }
+
+    #[test]
+    fn test_nearest_neighbour_couplings() {
+        let lattice = SquareLattice::new(4, 4);
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        spin_network.interactions.push((s0, s1, 1.0));
+
+        spin_network.place_spin(s0, (0, 0));
+        assert!(!spin_network.has_only_nearest_neighbour_couplings(&lattice));
+
+        spin_network.place_spin(s1, (1, 1));
+        assert!(!spin_network.has_only_nearest_neighbour_couplings(&lattice));
+
+        spin_network.place_spin(s1, (0, 1));
+        assert!(spin_network.has_only_nearest_neighbour_couplings(&lattice));
+
+        spin_network.place_spin(s1, (0, 0));
+        assert!(!spin_network.has_only_nearest_neighbour_couplings(&lattice));
+    }
+
+    #[test]
+    fn test_place_on_grid() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        let z = spin_network.add_binary_node(s0, s1, &AND::default());
+        let lattice = SquareLattice::new(9, 9);
+
+        let placed_spin_network = spin_network.place_on_grid(&lattice, 2.0).unwrap();
+
+        assert!(placed_spin_network.has_only_nearest_neighbour_couplings(&lattice));
+        let expected_ground_states: HashSet<_> = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .into_iter()
+            .map(|sample| sample.state)
+            .collect();
+        let actual_ground_states: HashSet<_> = placed_spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .into_iter()
+            .map(|sample| sample.state)
+            .collect();
+        assert_eq!(expected_ground_states, actual_ground_states);
+    }
+
+    #[test]
+    fn test_place_on_grid_too_small() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        spin_network.add_binary_node(s0, s1, &OR::default());
+
+        assert!(spin_network
+            .place_on_grid(&SquareLattice::new(3, 3), 2.0)
+            .is_none());
+    }
+}
@@ -1,5 +1,13 @@
+pub mod benchmarking;
+pub mod embedding;
+pub mod gauge;
+pub mod generators;
 mod hamiltonian;
+pub mod lattice;
 pub mod nodelib;
+pub mod observables;
+pub mod preprocessing;
+pub mod sample_set;
 pub mod solvers;
 pub mod spin_network;
 pub mod types;
@@ -7,9 +7,9 @@ pub struct COPY {
 }
 impl COPY {
     fn new(magnetic_field_strength: MagneticFieldStrength) -> Self {
-        return COPY {
+        COPY {
             magnetic_field_strength,
-        };
+        }
     }
 }
 impl Node for COPY {
@@ -265,7 +265,9 @@ mod tests {
         let copy_gate = COPY::new(0.0);
         let z = spin_network.add_unary_node(s0, &copy_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, z]))
+            .energies_and_states();
         let expected_ground_states = vec![(-1.0, vec![false, false]), (-1.0, vec![true, true])];
 
         assert_eq!(expected_ground_states, actual_ground_states)
@@ -278,7 +280,9 @@ mod tests {
         let not_gate = NOT::default();
         let z = spin_network.add_unary_node(s0, &not_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, z]))
+            .energies_and_states();
         let expected_ground_states = vec![(-1.0, vec![true, false]), (-1.0, vec![false, true])];
 
         assert_eq!(expected_ground_states, actual_ground_states)
@@ -292,7 +296,9 @@ mod tests {
         let and_gate = AND::default();
         let z = spin_network.add_binary_node(s0, s1, &and_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-3.5, vec![false, false, false]),
             (-3.5, vec![true, false, false]),
@@ -311,7 +317,9 @@ mod tests {
         let or_gate = OR::default();
         let z = spin_network.add_binary_node(s0, s1, &or_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-3.5, vec![false, false, false]),
             (-3.5, vec![true, false, true]),
@@ -330,7 +338,9 @@ mod tests {
         let nand_gate = NAND::default();
         let z = spin_network.add_binary_node(s0, s1, &nand_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-3.5, vec![false, false, true]),
             (-3.5, vec![true, false, true]),
@@ -349,7 +359,9 @@ mod tests {
         let nand_gate = NOR::default();
         let z = spin_network.add_binary_node(s0, s1, &nand_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-3.5, vec![false, false, true]),
             (-3.5, vec![true, false, false]),
@@ -368,7 +380,9 @@ mod tests {
         let xor_gate = XOR::default();
         let z = spin_network.add_binary_node(s0, s1, &xor_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-4.0, vec![false, false, false]),
             (-4.0, vec![true, false, true]),
@@ -387,7 +401,9 @@ mod tests {
         let xnor_gate = XNOR::default();
         let z = spin_network.add_binary_node(s0, s1, &xnor_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-4.0, vec![false, false, true]),
             (-4.0, vec![true, false, false]),
@@ -408,7 +424,9 @@ mod tests {
         let z_aux = spin_network.add_binary_node(s0, s1, &or_gate);
         let z = spin_network.add_binary_node(z_aux, s2, &or_gate);
 
-        let actual_ground_states = spin_network.find_all_ground_states(Some(vec![s0, s1, s2, z]));
+        let actual_ground_states = spin_network
+            .find_all_ground_states(Some(vec![s0, s1, s2, z]))
+            .energies_and_states();
         let expected_ground_states = vec![
             (-7.0, vec![false, false, false, false]),
             (-7.0, vec![true, false, false, true]),
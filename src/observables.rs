@@ -0,0 +1,279 @@
+use crate::sample_set::SampleSet;
+use crate::types::{Energy, SpinIndex, State};
+
+/// Returns the mean of `observable` over `sample_set`, where every sample counts as many times as it occurred.
+fn sample_mean(sample_set: &SampleSet, observable: impl Fn(Energy, &State) -> f64) -> f64 {
+    assert!(
+        sample_set.num_occurrences() > 0,
+        "Observables can only be estimated from at least one sample"
+    );
+    let total: f64 = sample_set
+        .iter()
+        .map(|sample| sample.num_occurrences as f64 * observable(sample.energy, &sample.state))
+        .sum();
+
+    total / sample_set.num_occurrences() as f64
+}
+
+/// Returns the magnetization per spin of `state`, i.e. the mean of its spins.
+fn magnetization_of(state: &State) -> f64 {
+    let total: f64 = state
+        .iter()
+        .map(|spin| if *spin { 1.0 } else { -1.0 })
+        .sum();
+
+    total / state.len().max(1) as f64
+}
+
+/// Returns the product of the spins `i` and `j` of `state`.
+fn spin_product(state: &State, i: SpinIndex, j: SpinIndex) -> f64 {
+    if state[i] == state[j] {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// Returns the mean energy of `sample_set`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::observables::mean_energy;
+/// use ernst::sample_set::{Sample, SampleSet};
+///
+/// let sample_set: SampleSet = vec![Sample::new(-1.0, vec![true, true]), Sample::new(1.0, vec![true, false])]
+///     .into_iter()
+///     .collect();
+///
+/// assert_eq!(0.0, mean_energy(&sample_set));
+/// ```
+pub fn mean_energy(sample_set: &SampleSet) -> Energy {
+    sample_mean(sample_set, |energy, _| energy as f64) as Energy
+}
+
+/// Returns the specific heat of `sample_set`, sampled at inverse temperature `beta`, from the fluctuations of its
+/// energy, i.e. `beta^2 (<E^2> - <E>^2)`. It's the specific heat of the whole spin glass, not per spin.
+pub fn specific_heat(sample_set: &SampleSet, beta: f32) -> f32 {
+    let mean_energy = sample_mean(sample_set, |energy, _| energy as f64);
+    let mean_squared_energy = sample_mean(sample_set, |energy, _| (energy as f64).powi(2));
+
+    ((beta as f64).powi(2) * (mean_squared_energy - mean_energy * mean_energy)) as f32
+}
+
+/// Returns the mean magnetization per spin of `sample_set`.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::observables::magnetization;
+/// use ernst::sample_set::{Sample, SampleSet};
+///
+/// let sample_set: SampleSet = vec![Sample::new(-1.0, vec![true, true]), Sample::new(1.0, vec![true, false])]
+///     .into_iter()
+///     .collect();
+///
+/// assert_eq!(0.5, magnetization(&sample_set));
+/// ```
+pub fn magnetization(sample_set: &SampleSet) -> f32 {
+    sample_mean(sample_set, |_, state| magnetization_of(state)) as f32
+}
+
+/// Returns the magnetic susceptibility of `sample_set`, sampled at inverse temperature `beta`, from the fluctuations
+/// of its magnetization per spin `m`, i.e. `beta N (<m^2> - <m>^2)`, where `N` is the number of spins.
+pub fn susceptibility(sample_set: &SampleSet, beta: f32) -> f32 {
+    let n = sample_set.samples.first().map_or(0, |sample| sample.state.len());
+    let mean_magnetization = sample_mean(sample_set, |_, state| magnetization_of(state));
+    let mean_squared_magnetization =
+        sample_mean(sample_set, |_, state| magnetization_of(state).powi(2));
+
+    (beta as f64
+        * n as f64
+        * (mean_squared_magnetization - mean_magnetization * mean_magnetization)) as f32
+}
+
+/// Returns the matrix of spin-spin correlations `<s_i s_j>` of `sample_set`. Subtract `<s_i><s_j>` from them to get
+/// the connected correlations.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::observables::spin_spin_correlations;
+/// use ernst::sample_set::{Sample, SampleSet};
+///
+/// let sample_set: SampleSet = vec![Sample::new(-1.0, vec![true, true]), Sample::new(-1.0, vec![false, false])]
+///     .into_iter()
+///     .collect();
+///
+/// assert_eq!(vec![vec![1.0, 1.0], vec![1.0, 1.0]], spin_spin_correlations(&sample_set));
+/// ```
+pub fn spin_spin_correlations(sample_set: &SampleSet) -> Vec<Vec<f32>> {
+    let n = sample_set.samples.first().map_or(0, |sample| sample.state.len());
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| sample_mean(sample_set, |_, state| spin_product(state, i, j)) as f32)
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the overlap between the states `first` and `second`, i.e. the mean of the products of their spins. It's 1
+/// when they are the same, and -1 when one is the other one with every spin flipped.
+pub fn overlap(first: &State, second: &State) -> f32 {
+    assert_eq!(
+        first.len(),
+        second.len(),
+        "Only states with the same number of spins overlap"
+    );
+    let total: f32 = first
+        .iter()
+        .zip(second)
+        .map(|(left, right)| if left == right { 1.0 } else { -1.0 })
+        .sum();
+
+    total / first.len().max(1) as f32
+}
+
+/// Returns the overlaps between two replicas of the same spin glass, whose samples are `first` and `second`. Samples
+/// are paired in the order they were drawn, and the number of occurrences of aggregated samples is ignored, so both
+/// sample sets should come straight from independent runs of a sampler.
+pub fn overlaps(first: &SampleSet, second: &SampleSet) -> Vec<f32> {
+    first
+        .iter()
+        .zip(second.iter())
+        .map(|(left, right)| overlap(&left.state, &right.state))
+        .collect()
+}
+
+/// Returns the Edwards-Anderson overlap between two replicas of the same spin glass, whose samples are `first` and
+/// `second`, as the mean of the absolute value of their [overlaps], so that it doesn't average out because of the
+/// symmetry under a flip of every spin. It approaches 1 when the replicas freeze in the same state, up to such a
+/// flip, and 0 in the paramagnetic phase.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::observables::edwards_anderson_overlap;
+/// use ernst::sample_set::{Sample, SampleSet};
+///
+/// let first: SampleSet = vec![Sample::new(-1.0, vec![true, true]), Sample::new(-1.0, vec![true, true])]
+///     .into_iter()
+///     .collect();
+/// let second: SampleSet = vec![Sample::new(-1.0, vec![false, false]), Sample::new(1.0, vec![true, false])]
+///     .into_iter()
+///     .collect();
+///
+/// assert_eq!(0.5, edwards_anderson_overlap(&first, &second));
+/// ```
+pub fn edwards_anderson_overlap(first: &SampleSet, second: &SampleSet) -> f32 {
+    let overlaps = overlaps(first, second);
+    assert!(
+        !overlaps.is_empty(),
+        "The overlap can only be estimated from at least one pair of samples"
+    );
+
+    overlaps.iter().map(|overlap| overlap.abs()).sum::<f32>() / overlaps.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::observables::{
+        edwards_anderson_overlap, magnetization, mean_energy, specific_heat,
+        spin_spin_correlations, susceptibility,
+    };
+    use crate::solvers::boltzmann_sampling::{sample_boltzmann, BoltzmannSamplingConfiguration};
+    use crate::solvers::energy;
+    use crate::types::{ExternalMagneticField, Interactions, State};
+
+    #[test]
+    fn test_observables_of_boltzmann_samples_match_exact_ones() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, -1.0), (2, 3, 1.0), (3, 0, 0.5)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.5, 0.0, -0.5, 0.3];
+        let beta = 0.8;
+        let states: Vec<State> = (0..16_usize)
+            .map(|bits| (0..4).map(|i| bits >> i & 1 == 1).collect())
+            .collect();
+        let weights: Vec<f64> = states
+            .iter()
+            .map(|state| {
+                (-beta as f64 * energy(&interactions, &external_magnetic_field, state) as f64).exp()
+            })
+            .collect();
+        let partition_function: f64 = weights.iter().sum();
+        let exact_mean = |observable: &dyn Fn(&State) -> f64| -> f64 {
+            states
+                .iter()
+                .zip(&weights)
+                .map(|(state, weight)| weight * observable(state))
+                .sum::<f64>()
+                / partition_function
+        };
+        let state_energy =
+            |state: &State| energy(&interactions, &external_magnetic_field, state) as f64;
+        let state_magnetization = |state: &State| {
+            state
+                .iter()
+                .map(|spin| if *spin { 0.25 } else { -0.25 })
+                .sum::<f64>()
+        };
+        let exact_mean_energy = exact_mean(&state_energy);
+        let exact_specific_heat = beta as f64
+            * beta as f64
+            * (exact_mean(&|state| state_energy(state).powi(2)) - exact_mean_energy.powi(2));
+        let exact_magnetization = exact_mean(&state_magnetization);
+        let exact_susceptibility = beta as f64
+            * 4.0
+            * (exact_mean(&|state| state_magnetization(state).powi(2))
+                - exact_magnetization.powi(2));
+        let exact_correlation = exact_mean(&|state| if state[0] == state[2] { 1.0 } else { -1.0 });
+        let boltzmann_sampling_configuration = BoltzmannSamplingConfiguration {
+            num_samples: 20000,
+            burn_in: 100,
+            thinning: 2,
+            ..Default::default()
+        };
+
+        let samples = sample_boltzmann(
+            &interactions,
+            &external_magnetic_field,
+            beta,
+            Some(&boltzmann_sampling_configuration),
+        );
+
+        assert!((mean_energy(&samples) as f64 - exact_mean_energy).abs() < 0.05);
+        assert!((specific_heat(&samples, beta) as f64 - exact_specific_heat).abs() < 0.1);
+        assert!((magnetization(&samples) as f64 - exact_magnetization).abs() < 0.02);
+        assert!((susceptibility(&samples, beta) as f64 - exact_susceptibility).abs() < 0.05);
+        assert!((spin_spin_correlations(&samples)[0][2] as f64 - exact_correlation).abs() < 0.03);
+        // Aggregating the samples doesn't change any estimate
+        assert!((mean_energy(&samples.aggregate()) - mean_energy(&samples)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_edwards_anderson_overlap_of_frozen_and_free_replicas() {
+        // A ferromagnetic chain is frozen at low temperature, in either of its two ground states
+        let interactions: Interactions = (0..9).map(|i| (i, i + 1, 1.0)).collect();
+        let external_magnetic_field: ExternalMagneticField = vec![0.0; 10];
+        let replica_with = |beta: f32, seed: u64| {
+            let boltzmann_sampling_configuration = BoltzmannSamplingConfiguration {
+                num_samples: 500,
+                seed,
+                ..Default::default()
+            };
+
+            sample_boltzmann(
+                &interactions,
+                &external_magnetic_field,
+                beta,
+                Some(&boltzmann_sampling_configuration),
+            )
+        };
+
+        let frozen_overlap = edwards_anderson_overlap(&replica_with(5.0, 1), &replica_with(5.0, 2));
+        let free_overlap = edwards_anderson_overlap(&replica_with(0.01, 1), &replica_with(0.01, 2));
+
+        assert!(frozen_overlap > 0.95);
+        assert!(free_overlap < 0.4);
+    }
+}
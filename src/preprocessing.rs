@@ -0,0 +1,539 @@
+//! Preprocessing of spin glasses before they're solved, which fixes the spins whose value in a ground state can be
+//! determined without solving the whole spin glass, and leaves a smaller spin glass over the other ones.
+
+use crate::sample_set::{Sample, SampleSet};
+use crate::types::{Energy, ExternalMagneticField, Interactions, SpinIndex, State};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// Flows and capacities below this are considered zero, so that rounding doesn't open residual arcs that aren't there.
+const FLOW_TOLERANCE: f64 = 1e-9;
+
+/// The spin glass that is left after fixing some of the spins of another one, as returned by [roof_duality].
+/// - `interactions`: interaction terms between the spins that are left, indexed from 0
+/// - `external_magnetic_field`: external magnetic field of the spins that are left, which includes the contribution of
+///   their interactions with the fixed spins
+/// - `energy_offset`: the energy of the fixed spins, so that the energy of every state of the original spin glass is
+///   the energy of the corresponding state of the reduced one plus this
+/// - `fixed_spins`: the value of every spin of the original spin glass, or None if it's left in the reduced one
+/// - `free_spins`: for every spin of the reduced spin glass, its index in the original one
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReducedProblem {
+    pub interactions: Interactions,
+    pub external_magnetic_field: ExternalMagneticField,
+    pub energy_offset: Energy,
+    pub fixed_spins: Vec<Option<bool>>,
+    pub free_spins: Vec<SpinIndex>,
+}
+
+impl ReducedProblem {
+    /// Maps `state` of the reduced spin glass back to the original one, filling in the fixed spins.
+    pub fn expand_state(&self, state: &State) -> State {
+        assert_eq!(
+            self.free_spins.len(),
+            state.len(),
+            "The state has to have a value for every spin of the reduced spin glass"
+        );
+        let mut expanded_state: State = self
+            .fixed_spins
+            .iter()
+            .map(|fixed_spin| fixed_spin.unwrap_or_default())
+            .collect();
+        for (spin, original_spin) in self.free_spins.iter().enumerate() {
+            expanded_state[*original_spin] = state[spin];
+        }
+
+        expanded_state
+    }
+    /// Maps `sample_set` of the reduced spin glass back to the original one, filling in the fixed spins and adding
+    /// `energy_offset` to every energy. All the other metadata, and the traces, are kept.
+    pub fn expand(&self, sample_set: &SampleSet) -> SampleSet {
+        SampleSet {
+            samples: sample_set
+                .iter()
+                .map(|sample| Sample {
+                    energy: sample.energy + self.energy_offset,
+                    state: self.expand_state(&sample.state),
+                    ..sample.clone()
+                })
+                .collect(),
+            traces: sample_set.traces.clone(),
+        }
+    }
+}
+
+/// Fixes the spins of the spin glass, whose interaction terms and external magnetic field are given as the
+/// `interactions` and `external_magnetic_field` arguments, whose values in a ground state follow from its roof dual,
+/// and returns the reduced spin glass over the other ones. Every ground state of the reduced spin glass, once expanded
+/// with [ReducedProblem::expand], is a ground state of the original one, although the fixed spins may take other
+/// values in other ground states.
+///
+/// The spin glass is written as a posiform, i.e. a sum of products of one or two literals, `x_i` or `1 - x_i`, with
+/// positive coefficients, which is turned into the implication network of QPBO: every term `c u v` adds the arcs
+/// `u -> not v` and `v -> not u`, of capacity `c / 2`, and linear terms are products with the source, which stands
+/// for true. After a maximum flow, made symmetric with respect to negating every literal, the residual network is a
+/// set of implications that some ground state satisfies, and spins are fixed from its strongly connected components
+/// like in 2-SAT. This is repeated on the reduced spin glass until no more spins are fixed.
+///
+/// Networks of logic gates like AND, OR and NOT, whose inputs are clamped by their magnetic fields, collapse
+/// completely, since the values flow from the inputs through every gate. The auxiliary spins of frustrated gadgets,
+/// like the one of XOR, are usually left free.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::nodelib::logic_gates::AND;
+/// use ernst::preprocessing::roof_duality;
+/// use ernst::solvers::energy;
+/// use ernst::spin_network::SpinNetwork;
+///
+/// let mut spin_network = SpinNetwork::new();
+/// let s0 = spin_network.add_input_node(1.0);
+/// let s1 = spin_network.add_input_node(1.0);
+/// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+///
+/// let reduced_problem = roof_duality(&spin_network.interactions, &spin_network.external_magnetic_field);
+///
+/// assert!(reduced_problem.free_spins.is_empty());
+/// let ground_state = reduced_problem.expand_state(&vec![]);
+/// assert_eq!(vec![true, true, true], vec![ground_state[s0], ground_state[s1], ground_state[z]]);
+/// assert_eq!(
+///     reduced_problem.energy_offset,
+///     energy(&spin_network.interactions, &spin_network.external_magnetic_field, &ground_state)
+/// );
+/// ```
+pub fn roof_duality(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+) -> ReducedProblem {
+    let mut reduced_problem = fix_spins(
+        interactions,
+        external_magnetic_field,
+        vec![None; external_magnetic_field.len()],
+    );
+    // Fixing spins turns their interactions into fields, which often lets the roof dual of what's left fix more of them
+    loop {
+        let persistent_spins = persistent_spins(
+            &reduced_problem.interactions,
+            &reduced_problem.external_magnetic_field,
+        );
+        if persistent_spins.iter().all(Option::is_none) {
+            return reduced_problem;
+        }
+        let mut fixed_spins = reduced_problem.fixed_spins.clone();
+        for (spin, value) in persistent_spins.into_iter().enumerate() {
+            if value.is_some() {
+                fixed_spins[reduced_problem.free_spins[spin]] = value;
+            }
+        }
+        reduced_problem = fix_spins(interactions, external_magnetic_field, fixed_spins);
+    }
+}
+
+/// Returns the spin glass, whose interaction terms and external magnetic field are given as the `interactions` and
+/// `external_magnetic_field` arguments, reduced to the spins that `fixed_spins` leaves free.
+fn fix_spins(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    fixed_spins: Vec<Option<bool>>,
+) -> ReducedProblem {
+    let n = external_magnetic_field.len();
+    let free_spins: Vec<SpinIndex> = (0..n).filter(|i| fixed_spins[*i].is_none()).collect();
+    let mut reduced_index = vec![None; n];
+    for (spin, original_spin) in free_spins.iter().enumerate() {
+        reduced_index[*original_spin] = Some(spin);
+    }
+    let spin_value = |spin: Option<bool>| if spin == Some(true) { 1.0 } else { -1.0 };
+
+    let mut reduced_interactions = vec![];
+    let mut reduced_external_magnetic_field: ExternalMagneticField = free_spins
+        .iter()
+        .map(|original_spin| external_magnetic_field[*original_spin])
+        .collect();
+    let mut energy_offset = 0.0;
+    for (i, magnetic_field_strength) in external_magnetic_field.iter().enumerate() {
+        if fixed_spins[i].is_some() {
+            energy_offset -= magnetic_field_strength * spin_value(fixed_spins[i]);
+        }
+    }
+    for (i, j, interaction_strength) in interactions {
+        match (reduced_index[*i], reduced_index[*j]) {
+            (Some(reduced_i), Some(reduced_j)) => {
+                reduced_interactions.push((reduced_i, reduced_j, *interaction_strength))
+            }
+            (Some(reduced_i), None) => {
+                reduced_external_magnetic_field[reduced_i] +=
+                    interaction_strength * spin_value(fixed_spins[*j])
+            }
+            (None, Some(reduced_j)) => {
+                reduced_external_magnetic_field[reduced_j] +=
+                    interaction_strength * spin_value(fixed_spins[*i])
+            }
+            (None, None) => {
+                energy_offset -=
+                    interaction_strength * spin_value(fixed_spins[*i]) * spin_value(fixed_spins[*j])
+            }
+        }
+    }
+
+    ReducedProblem {
+        interactions: reduced_interactions,
+        external_magnetic_field: reduced_external_magnetic_field,
+        energy_offset,
+        fixed_spins,
+        free_spins,
+    }
+}
+
+/// An arc of the implication network, whose residual capacity is `capacity - flow`.
+struct Arc {
+    to: usize,
+    capacity: f64,
+    flow: f64,
+}
+
+/// The implication network of the posiform of a spin glass. The literal `x_i` is the node `2i`, its negation the node
+/// `2i + 1`, and the source and the sink, which stand for true and false, are the last two, so that the negation of
+/// every node is the node with its lowest bit flipped. Every arc is stored next to its reverse arc, i.e. arc `a` and
+/// `a ^ 1`, and every pair of arcs next to the pair of arcs between the negated literals.
+struct ImplicationNetwork {
+    arcs: Vec<Arc>,
+    outgoing: Vec<Vec<usize>>,
+}
+
+impl ImplicationNetwork {
+    fn new(num_nodes: usize) -> Self {
+        ImplicationNetwork {
+            arcs: vec![],
+            outgoing: vec![vec![]; num_nodes],
+        }
+    }
+    /// Adds the term `coefficient u v` of the posiform, i.e. the arcs `u -> not v` and `v -> not u`.
+    fn add_term(&mut self, u: usize, v: usize, coefficient: f64) {
+        if coefficient <= 0.0 {
+            return;
+        }
+        for (from, to) in [(u, v ^ 1), (v, u ^ 1)] {
+            self.outgoing[from].push(self.arcs.len());
+            self.arcs.push(Arc {
+                to,
+                capacity: coefficient / 2.0,
+                flow: 0.0,
+            });
+            self.outgoing[to].push(self.arcs.len());
+            self.arcs.push(Arc {
+                to: from,
+                capacity: 0.0,
+                flow: 0.0,
+            });
+        }
+    }
+    fn residual_capacity(&self, arc: usize) -> f64 {
+        self.arcs[arc].capacity - self.arcs[arc].flow
+    }
+    /// Pushes a maximum flow from `source` to `sink`, with Dinic's algorithm.
+    fn maximize_flow(&mut self, source: usize, sink: usize) {
+        loop {
+            let levels = self.levels(source);
+            if levels[sink].is_none() {
+                return;
+            }
+            let mut next_arc = vec![0; self.outgoing.len()];
+            while self.augment(source, sink, &levels, &mut next_arc) > 0.0 {}
+        }
+    }
+    /// Returns the distance of every node from `source` in the residual network, if it's reachable.
+    fn levels(&self, source: usize) -> Vec<Option<usize>> {
+        let mut levels = vec![None; self.outgoing.len()];
+        levels[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for arc in &self.outgoing[node] {
+                let to = self.arcs[*arc].to;
+                if levels[to].is_none() && self.residual_capacity(*arc) > FLOW_TOLERANCE {
+                    levels[to] = levels[node].map(|level| level + 1);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        levels
+    }
+    /// Pushes flow along a path of increasing levels from `source` to `sink`, and returns how much it pushed. It's a
+    /// depth first search with an explicit stack of arcs instead of recursion, since the path can be as long as the
+    /// spin glass, e.g. on long chains of spins.
+    fn augment(
+        &mut self,
+        source: usize,
+        sink: usize,
+        levels: &[Option<usize>],
+        next_arc: &mut [usize],
+    ) -> f64 {
+        let mut path: Vec<usize> = vec![];
+        loop {
+            let node = path.last().map_or(source, |arc| self.arcs[*arc].to);
+            if node == sink {
+                let pushed = path
+                    .iter()
+                    .map(|arc| self.residual_capacity(*arc))
+                    .fold(f64::INFINITY, f64::min);
+                for arc in path {
+                    self.arcs[arc].flow += pushed;
+                    self.arcs[arc ^ 1].flow -= pushed;
+                }
+                return pushed;
+            }
+            let mut advanced = false;
+            while next_arc[node] < self.outgoing[node].len() {
+                let arc = self.outgoing[node][next_arc[node]];
+                let to = self.arcs[arc].to;
+                if self.residual_capacity(arc) > FLOW_TOLERANCE
+                    && levels[to] == levels[node].map(|level| level + 1)
+                {
+                    path.push(arc);
+                    advanced = true;
+                    break;
+                }
+                next_arc[node] += 1;
+            }
+            if advanced {
+                continue;
+            }
+            // The node is a dead end, so the arc that leads to it is skipped from now on
+            match path.pop() {
+                Some(arc) => next_arc[self.arcs[arc ^ 1].to] += 1,
+                None => return 0.0,
+            }
+        }
+    }
+    /// Replaces the flow of every pair of arcs between negated literals with their mean, which is still a maximum flow.
+    fn symmetrize_flow(&mut self) {
+        // The pairs of arcs of a term are added together, i.e. arcs 4k and 4k + 2, along with their reverse arcs
+        for term in 0..self.arcs.len() / 4 {
+            let mean_flow = (self.arcs[4 * term].flow + self.arcs[4 * term + 2].flow) / 2.0;
+            self.arcs[4 * term].flow = mean_flow;
+            self.arcs[4 * term + 1].flow = -mean_flow;
+            self.arcs[4 * term + 2].flow = mean_flow;
+            self.arcs[4 * term + 3].flow = -mean_flow;
+        }
+    }
+    /// Returns the strongly connected component of every node in the residual network, numbered in reverse
+    /// topological order, i.e. there's no residual path from a component to one with a higher number. It's Tarjan's
+    /// algorithm, with an explicit stack instead of recursion, so that large spin glasses don't overflow the call stack.
+    fn strongly_connected_components(&self) -> Vec<usize> {
+        let num_nodes = self.outgoing.len();
+        let mut index = vec![usize::MAX; num_nodes];
+        let mut lowlink = vec![0; num_nodes];
+        let mut on_stack = vec![false; num_nodes];
+        let mut stack = vec![];
+        let mut components = vec![usize::MAX; num_nodes];
+        let mut next_index = 0;
+        let mut num_components = 0;
+
+        for root in 0..num_nodes {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            let mut call_stack = vec![(root, 0)];
+            index[root] = next_index;
+            lowlink[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some((node, position)) = call_stack.last().copied() {
+                if position < self.outgoing[node].len() {
+                    call_stack.last_mut().unwrap().1 += 1;
+                    let arc = self.outgoing[node][position];
+                    if self.residual_capacity(arc) <= FLOW_TOLERANCE {
+                        continue;
+                    }
+                    let to = self.arcs[arc].to;
+                    if index[to] == usize::MAX {
+                        index[to] = next_index;
+                        lowlink[to] = next_index;
+                        next_index += 1;
+                        stack.push(to);
+                        on_stack[to] = true;
+                        call_stack.push((to, 0));
+                    } else if on_stack[to] {
+                        lowlink[node] = lowlink[node].min(index[to]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some((parent, _)) = call_stack.last() {
+                    lowlink[*parent] = lowlink[*parent].min(lowlink[node]);
+                }
+                if lowlink[node] == index[node] {
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        components[member] = num_components;
+                        if member == node {
+                            break;
+                        }
+                    }
+                    num_components += 1;
+                }
+            }
+        }
+
+        components
+    }
+}
+
+/// Returns the value of every spin that the roof dual of the spin glass fixes, or None if it doesn't.
+fn persistent_spins(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+) -> Vec<Option<bool>> {
+    let n = external_magnetic_field.len();
+    // With s = 2x - 1, every term becomes a quadratic pseudo-boolean function of x, up to a constant
+    let mut linear_coefficients: Vec<f64> = external_magnetic_field
+        .iter()
+        .map(|magnetic_field_strength| -2.0 * *magnetic_field_strength as f64)
+        .collect();
+    let mut quadratic_terms = vec![];
+    for (i, j, interaction_strength) in interactions {
+        let interaction_strength = *interaction_strength as f64;
+        linear_coefficients[*i] += 2.0 * interaction_strength;
+        linear_coefficients[*j] += 2.0 * interaction_strength;
+        quadratic_terms.push((*i, *j, -4.0 * interaction_strength));
+    }
+
+    let source = 2 * n;
+    let sink = 2 * n + 1;
+    let mut implication_network = ImplicationNetwork::new(2 * n + 2);
+    for (i, j, coefficient) in quadratic_terms {
+        if coefficient > 0.0 {
+            implication_network.add_term(2 * i, 2 * j, coefficient);
+        } else {
+            // c x_i x_j = c x_i - c x_i (1 - x_j)
+            linear_coefficients[i] += coefficient;
+            implication_network.add_term(2 * i, 2 * j + 1, -coefficient);
+        }
+    }
+    for (i, coefficient) in linear_coefficients.iter().enumerate() {
+        if *coefficient > 0.0 {
+            implication_network.add_term(2 * i, source, *coefficient);
+        } else {
+            // c x_i = c - c (1 - x_i)
+            implication_network.add_term(2 * i + 1, source, -coefficient);
+        }
+    }
+
+    implication_network.maximize_flow(source, sink);
+    implication_network.symmetrize_flow();
+    // Every residual arc is an implication between literals that some ground state satisfies, and the source is true,
+    // so, like in 2-SAT, a literal can be set to true if its component comes after the one of its negation in
+    // topological order, unless they are the same component. The arc from the sink to the source makes the source true,
+    // and has no reverse arc, since no more flow is pushed
+    implication_network.outgoing[sink].push(implication_network.arcs.len());
+    implication_network.arcs.push(Arc {
+        to: source,
+        capacity: f64::INFINITY,
+        flow: 0.0,
+    });
+    let components = implication_network.strongly_connected_components();
+    (0..n)
+        .map(|i| match components[2 * i].cmp(&components[2 * i + 1]) {
+            Ordering::Less => Some(true),
+            Ordering::Greater => Some(false),
+            Ordering::Equal => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::nodelib::logic_gates::{AND, NOT, OR};
+    use crate::preprocessing::roof_duality;
+    use crate::solvers::{energy, find_all_ground_states};
+    use crate::spin_network::SpinNetwork;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_roof_duality_keeps_a_ground_state() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut total_fixed_spins = 0;
+        for seed in 0..50 {
+            let (interactions, mut external_magnetic_field) =
+                edwards_anderson_2d(3, 4, CouplingDistribution::Gaussian, seed);
+            for magnetic_field_strength in external_magnetic_field.iter_mut() {
+                *magnetic_field_strength = rng.gen_range(-2.0..2.0);
+            }
+            let ground_energy = find_all_ground_states(&interactions, &external_magnetic_field)
+                .lowest_energy()
+                .unwrap();
+
+            let reduced_problem = roof_duality(&interactions, &external_magnetic_field);
+
+            let reduced_ground_states = find_all_ground_states(
+                &reduced_problem.interactions,
+                &reduced_problem.external_magnetic_field,
+            );
+            for ground_state in reduced_problem.expand(&reduced_ground_states).iter() {
+                assert!((ground_energy - ground_state.energy).abs() < 1e-4);
+                assert!(
+                    (energy(&interactions, &external_magnetic_field, &ground_state.state)
+                        - ground_state.energy)
+                        .abs()
+                        < 1e-4
+                );
+            }
+            total_fixed_spins += 12 - reduced_problem.free_spins.len();
+        }
+        assert!(total_fixed_spins > 0);
+    }
+
+    #[test]
+    fn test_roof_duality_collapses_clamped_circuits() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(1.0);
+        let s1 = spin_network.add_input_node(-1.0);
+        let s2 = spin_network.add_input_node(1.0);
+        let z_or = spin_network.add_binary_node(s0, s1, &OR::default());
+        let z_and = spin_network.add_binary_node(z_or, s2, &AND::default());
+        let z = spin_network.add_unary_node(z_and, &NOT::default());
+        let ground_states = find_all_ground_states(
+            &spin_network.interactions,
+            &spin_network.external_magnetic_field,
+        );
+
+        let reduced_problem = roof_duality(
+            &spin_network.interactions,
+            &spin_network.external_magnetic_field,
+        );
+
+        assert!(reduced_problem.free_spins.is_empty());
+        assert_eq!(
+            ground_states,
+            reduced_problem.expand(&find_all_ground_states(
+                &reduced_problem.interactions,
+                &reduced_problem.external_magnetic_field
+            ))
+        );
+        assert_eq!(Some(false), reduced_problem.fixed_spins[z]);
+    }
+
+    #[test]
+    fn test_roof_duality_fixes_long_chains() {
+        let n = 100_000;
+        let interactions = (0..n - 1).map(|i| (i, i + 1, 1.0)).collect();
+        let mut external_magnetic_field = vec![0.0; n];
+        external_magnetic_field[0] = 1.0;
+        external_magnetic_field[n - 1] = 1.0;
+
+        let reduced_problem = roof_duality(&interactions, &external_magnetic_field);
+
+        assert!(reduced_problem.free_spins.is_empty());
+        assert!(reduced_problem
+            .fixed_spins
+            .iter()
+            .all(|spin| *spin == Some(true)));
+        assert_eq!(-(n as f32) - 1.0, reduced_problem.energy_offset);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::solvers::Epoch;
+use crate::types::{Energy, SpinIndex, State};
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+
+/// Energies closer than this are considered the same energy level, so that floating point rounding doesn't split
+/// levels in two.
+pub const ENERGY_RESOLUTION: Energy = 1e-4;
+
+/// Returns the energy level of `energy`, i.e. the multiple of [ENERGY_RESOLUTION] that it rounds to. Two energies are
+/// the same level if and only if they have the same energy level, which is how the exact solvers group their states.
+pub fn energy_level(energy: Energy) -> i64 {
+    (energy / ENERGY_RESOLUTION).round() as i64
+}
+
+/// Returns whether `energy`, of a state that a solver returned, is, up to floating point rounding, as low as
+/// `ground_state_energy`, i.e. within [ENERGY_RESOLUTION] of it, relative to its magnitude when it's above 1. Unlike
+/// [energy_level], it's meant for energies that were computed differently, e.g. incrementally during a sampling,
+/// whose rounding errors grow with their magnitude.
+pub fn is_ground_state_energy(energy: Energy, ground_state_energy: Energy) -> bool {
+    energy - ground_state_energy <= ENERGY_RESOLUTION * ground_state_energy.abs().max(1.0)
+}
+
+/// A Sample is a state returned by a solver, alongside its energy and some metadata.
+/// - `energy`: energy of the state
+/// - `state`: the state itself
+/// - `num_occurrences`: how many times the solver returned this state
+/// - `epoch`: the sweep in which the state was reached, for solvers that have sweeps
+/// - `chain_id`: the read, or Markov chain, that returned the state, for solvers that have them
+/// - `chain_break_fraction`: the fraction of broken chains, for states that were unembedded
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub energy: Energy,
+    pub state: State,
+    pub num_occurrences: usize,
+    pub epoch: Option<Epoch>,
+    pub chain_id: Option<usize>,
+    pub chain_break_fraction: Option<f32>,
+}
+
+impl Sample {
+    /// Creates a new Sample that occurred once and has no metadata.
+    pub fn new(energy: Energy, state: State) -> Self {
+        Sample {
+            energy,
+            state,
+            num_occurrences: 1,
+            epoch: None,
+            chain_id: None,
+            chain_break_fraction: None,
+        }
+    }
+}
+
+/// A step of an [AnnealingTrace]: the flip of `spin`, which took the state to `energy`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AcceptedMove {
+    pub spin: SpinIndex,
+    pub energy: Energy,
+}
+
+/// A move of an [AnnealingTrace] that flipped several spins at once, i.e. a cluster or block move: the flip of
+/// `spins`, which took the state to `energy`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AcceptedMultiSpinMove {
+    pub spins: Vec<SpinIndex>,
+    pub energy: Energy,
+}
+
+/// What happened during a single sweep of an [AnnealingTrace].
+/// - `sweep`: index of the sweep
+/// - `temperature`: temperature of the sweep
+/// - `accepted_moves`: the single spin flips that were accepted, in the order they happened
+/// - `rejected_moves`: how many of the single spin flips that were attempted were rejected
+/// - `accepted_cluster_moves`: the cluster moves that were accepted after the single spin flips, if any
+/// - `rejected_cluster_moves`: how many of the cluster moves that were attempted were rejected, or didn't flip any spin
+/// - `accepted_block_moves`: the block moves that were accepted after the cluster moves, if any
+/// - `rejected_block_moves`: how many of the block moves that were attempted were rejected
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepTrace {
+    pub sweep: Epoch,
+    pub temperature: f32,
+    pub accepted_moves: Vec<AcceptedMove>,
+    pub rejected_moves: usize,
+    pub accepted_cluster_moves: Vec<AcceptedMultiSpinMove>,
+    pub rejected_cluster_moves: usize,
+    pub accepted_block_moves: Vec<AcceptedMultiSpinMove>,
+    pub rejected_block_moves: usize,
+}
+
+/// The full trajectory of a read, or Markov chain, of a solver: the state it started from, and every move it made
+/// since then, sweep by sweep.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::sample_set::{AcceptedMove, AnnealingTrace, SweepTrace};
+///
+/// let annealing_trace = AnnealingTrace {
+///     chain_id: 0,
+///     initial_state: vec![true, false],
+///     initial_energy: 1.0,
+///     sweeps: vec![SweepTrace {
+///         sweep: 0,
+///         temperature: 1.0,
+///         accepted_moves: vec![AcceptedMove { spin: 1, energy: -1.0 }],
+///         rejected_moves: 1,
+///         accepted_cluster_moves: vec![],
+///         rejected_cluster_moves: 0,
+///         accepted_block_moves: vec![],
+///         rejected_block_moves: 0,
+///     }],
+/// };
+///
+/// assert_eq!(
+///     vec![(0, -1.0, vec![true, true])],
+///     annealing_trace.trajectory().collect::<Vec<_>>()
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnealingTrace {
+    pub chain_id: usize,
+    pub initial_state: State,
+    pub initial_energy: Energy,
+    pub sweeps: Vec<SweepTrace>,
+}
+
+impl AnnealingTrace {
+    /// Replays the trace, returning the sweep, energy and state after every accepted move. Within a sweep, the single
+    /// spin flips come first, then the cluster moves, and then the block moves, which is the order in which
+    /// [crate::solvers::simulated_annealing] makes them.
+    pub fn trajectory(&self) -> impl Iterator<Item = (Epoch, Energy, State)> + '_ {
+        let mut state = self.initial_state.clone();
+        self.sweeps.iter().flat_map(move |sweep_trace| {
+            let single_spin_moves = sweep_trace.accepted_moves.iter().map(|accepted_move| {
+                (
+                    std::slice::from_ref(&accepted_move.spin),
+                    accepted_move.energy,
+                )
+            });
+            let multi_spin_moves = sweep_trace
+                .accepted_cluster_moves
+                .iter()
+                .chain(&sweep_trace.accepted_block_moves)
+                .map(|accepted_move| (accepted_move.spins.as_slice(), accepted_move.energy));
+            single_spin_moves
+                .chain(multi_spin_moves)
+                .map(|(spins, energy)| {
+                    for spin in spins {
+                        state[*spin] = !state[*spin];
+                    }
+                    (sweep_trace.sweep, energy, state.clone())
+                })
+                .collect::<Vec<_>>()
+        })
+    }
+    /// Returns how many moves were accepted, whether single spin flips, cluster moves or block moves.
+    pub fn num_accepted_moves(&self) -> usize {
+        self.sweeps
+            .iter()
+            .map(|sweep_trace| {
+                sweep_trace.accepted_moves.len()
+                    + sweep_trace.accepted_cluster_moves.len()
+                    + sweep_trace.accepted_block_moves.len()
+            })
+            .sum()
+    }
+    /// Returns how many moves were rejected, whether single spin flips, cluster moves or block moves.
+    pub fn num_rejected_moves(&self) -> usize {
+        self.sweeps
+            .iter()
+            .map(|sweep_trace| {
+                sweep_trace.rejected_moves
+                    + sweep_trace.rejected_cluster_moves
+                    + sweep_trace.rejected_block_moves
+            })
+            .sum()
+    }
+}
+
+/// A SampleSet is the collection of [Sample]s that every solver returns. Solvers that are asked to trace their
+/// reads also return an [AnnealingTrace] of each one of them in `traces`. The traces refer to the spins the solver
+/// worked with, so they are kept as they are when the SampleSet is aggregated, filtered, projected or unembedded.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::sample_set::{Sample, SampleSet};
+///
+/// let sample_set: SampleSet = vec![
+///     Sample::new(-1.0, vec![true, true]),
+///     Sample::new(1.0, vec![true, false]),
+///     Sample::new(-1.0, vec![true, true]),
+///     Sample::new(-1.0, vec![false, false]),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let aggregated_sample_set = sample_set.aggregate();
+/// let lowest_sample_set = aggregated_sample_set.lowest();
+///
+/// assert_eq!(3, aggregated_sample_set.len());
+/// assert_eq!(
+///     vec![(-1.0, vec![true, true]), (-1.0, vec![false, false])],
+///     lowest_sample_set.energies_and_states()
+/// );
+/// assert_eq!(2, lowest_sample_set.samples[0].num_occurrences);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SampleSet {
+    pub samples: Vec<Sample>,
+    pub traces: Vec<AnnealingTrace>,
+}
+
+impl SampleSet {
+    /// Creates a new empty SampleSet.
+    pub fn new() -> Self {
+        SampleSet {
+            samples: vec![],
+            traces: vec![],
+        }
+    }
+    fn with_samples(&self, samples: Vec<Sample>) -> SampleSet {
+        SampleSet {
+            samples,
+            traces: self.traces.clone(),
+        }
+    }
+    pub fn push(&mut self, sample: Sample) {
+        self.samples.push(sample);
+    }
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, Sample> {
+        self.samples.iter()
+    }
+    /// Returns the total number of occurrences of all samples.
+    pub fn num_occurrences(&self) -> usize {
+        self.samples
+            .iter()
+            .map(|sample| sample.num_occurrences)
+            .sum()
+    }
+    /// Returns the lowest energy among all samples, if there are any.
+    pub fn lowest_energy(&self) -> Option<Energy> {
+        self.samples
+            .iter()
+            .map(|sample| sample.energy)
+            .reduce(Energy::min)
+    }
+    /// Returns the samples of lowest energy, in their original order.
+    pub fn lowest(&self) -> SampleSet {
+        match self.lowest_energy() {
+            Some(lowest_energy) => self.with_samples(
+                self.samples
+                    .iter()
+                    .filter(|sample| is_ground_state_energy(sample.energy, lowest_energy))
+                    .cloned()
+                    .collect(),
+            ),
+            None => self.with_samples(vec![]),
+        }
+    }
+    /// Merges the samples that have the same state into a single one, whose number of occurrences is the sum of
+    /// theirs. The metadata of the first one is kept, and the result is sorted by energy.
+    pub fn aggregate(&self) -> SampleSet {
+        let mut index_of_state: HashMap<&State, usize> = HashMap::new();
+        let mut aggregated_samples: Vec<Sample> = vec![];
+        for sample in &self.samples {
+            match index_of_state.get(&sample.state) {
+                Some(index) => aggregated_samples[*index].num_occurrences += sample.num_occurrences,
+                None => {
+                    index_of_state.insert(&sample.state, aggregated_samples.len());
+                    aggregated_samples.push(sample.clone());
+                }
+            }
+        }
+        aggregated_samples.sort_by(|left, right| left.energy.total_cmp(&right.energy));
+
+        self.with_samples(aggregated_samples)
+    }
+    /// Returns the samples whose energy lies within `energy_range`.
+    pub fn filter_by_energy(&self, energy_range: impl RangeBounds<Energy>) -> SampleSet {
+        self.with_samples(
+            self.samples
+                .iter()
+                .filter(|sample| energy_range.contains(&sample.energy))
+                .cloned()
+                .collect(),
+        )
+    }
+    /// Projects the state of every sample according to `spin_ordering`, i.e. the i-th spin of the projected state is
+    /// the `spin_ordering[i]`-th spin of the original one. Energies and metadata are left untouched.
+    pub fn project(&self, spin_ordering: &[SpinIndex]) -> SampleSet {
+        self.with_samples(
+            self.samples
+                .iter()
+                .map(|sample| Sample {
+                    state: spin_ordering
+                        .iter()
+                        .map(|spin_index| sample.state[*spin_index])
+                        .collect(),
+                    ..sample.clone()
+                })
+                .collect(),
+        )
+    }
+    /// Flips the spins of the state of every sample, and of the initial state of every trace, for which `gauge` is true.
+    /// It maps the samples of a spin glass transformed with [crate::gauge::apply_gauge] back to the original one, and
+    /// vice versa, since energies don't change. See [crate::gauge] for more details.
+    pub fn apply_gauge(&self, gauge: &[bool]) -> SampleSet {
+        let gauged_state = |state: &State| -> State {
+            assert_eq!(
+                state.len(),
+                gauge.len(),
+                "The gauge needs a value for every spin"
+            );
+            state
+                .iter()
+                .zip(gauge)
+                .map(|(spin, flip)| spin ^ flip)
+                .collect()
+        };
+        SampleSet {
+            samples: self
+                .samples
+                .iter()
+                .map(|sample| Sample {
+                    state: gauged_state(&sample.state),
+                    ..sample.clone()
+                })
+                .collect(),
+            traces: self
+                .traces
+                .iter()
+                .map(|trace| AnnealingTrace {
+                    initial_state: gauged_state(&trace.initial_state),
+                    ..trace.clone()
+                })
+                .collect(),
+        }
+    }
+    /// Returns the energy and state of every sample.
+    pub fn energies_and_states(&self) -> Vec<(Energy, State)> {
+        self.samples
+            .iter()
+            .map(|sample| (sample.energy, sample.state.clone()))
+            .collect()
+    }
+}
+
+impl FromIterator<Sample> for SampleSet {
+    fn from_iter<T: IntoIterator<Item = Sample>>(iter: T) -> Self {
+        SampleSet {
+            samples: iter.into_iter().collect(),
+            traces: vec![],
+        }
+    }
+}
+
+impl IntoIterator for SampleSet {
+    type Item = Sample;
+    type IntoIter = std::vec::IntoIter<Sample>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.samples.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SampleSet {
+    type Item = &'a Sample;
+    type IntoIter = std::slice::Iter<'a, Sample>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.samples.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sample_set::{Sample, SampleSet};
+
+    fn sample_set() -> SampleSet {
+        vec![
+            Sample {
+                chain_id: Some(0),
+                ..Sample::new(-2.0, vec![true, false, true])
+            },
+            Sample {
+                chain_id: Some(1),
+                ..Sample::new(0.0, vec![false, false, true])
+            },
+            Sample {
+                chain_id: Some(2),
+                ..Sample::new(-2.0, vec![true, false, true])
+            },
+            Sample {
+                chain_id: Some(3),
+                ..Sample::new(-1.0, vec![true, true, false])
+            },
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let aggregated_sample_set = sample_set().aggregate();
+
+        assert_eq!(
+            vec![
+                (-2.0, vec![true, false, true]),
+                (-1.0, vec![true, true, false]),
+                (0.0, vec![false, false, true]),
+            ],
+            aggregated_sample_set.energies_and_states()
+        );
+        assert_eq!(2, aggregated_sample_set.samples[0].num_occurrences);
+        assert_eq!(Some(0), aggregated_sample_set.samples[0].chain_id);
+        assert_eq!(4, aggregated_sample_set.num_occurrences());
+    }
+
+    #[test]
+    fn test_lowest_and_filter_by_energy() {
+        let sample_set = sample_set();
+
+        assert_eq!(Some(-2.0), sample_set.lowest_energy());
+        assert_eq!(2, sample_set.lowest().len());
+        assert_eq!(3, sample_set.filter_by_energy(..0.0).len());
+        assert_eq!(2, sample_set.filter_by_energy(-1.0..=0.0).len());
+        assert!(SampleSet::new().lowest().is_empty());
+    }
+
+    #[test]
+    fn test_project() {
+        let projected_sample_set = sample_set().project(&[2, 0]);
+
+        assert_eq!(
+            vec![
+                (-2.0, vec![true, true]),
+                (0.0, vec![true, false]),
+                (-2.0, vec![true, true]),
+                (-1.0, vec![false, true]),
+            ],
+            projected_sample_set.energies_and_states()
+        );
+        assert_eq!(Some(3), projected_sample_set.samples[3].chain_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "The gauge needs a value for every spin")]
+    fn test_apply_gauge_of_the_wrong_length() {
+        sample_set().apply_gauge(&[true, false]);
+    }
+}
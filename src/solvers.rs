@@ -1,12 +1,35 @@
-use crate::hamiltonian::TwoLocalHamiltonian;
+use crate::hamiltonian::{SparseCouplings, SpinChain, TwoLocalHamiltonian};
+use crate::sample_set::{
+    energy_level, AcceptedMove, AcceptedMultiSpinMove, AnnealingTrace, Sample, SampleSet,
+    SweepTrace,
+};
+use crate::solvers::block_moves::{block_sweep, LogicalBlock};
+use crate::solvers::cluster_moves::{houdayer_move, swendsen_wang_move, wolff_move, ClusterMove};
+use crate::solvers::density_of_states::DensityOfStates;
+use crate::solvers::observers::{Observer, SweepReport};
+use crate::solvers::schedules::{automatic_temperatures, AnnealingSchedule, Geometric};
 use crate::types::{
     CompactState, ComparableEnergy, Energy, ExternalMagneticField, Interactions, SpinIndex, State,
     Temperature,
 };
-use indexmap::IndexSet;
 use ordered_float::{Float, OrderedFloat};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub mod block_moves;
+pub mod boltzmann_sampling;
+pub mod cluster_moves;
+pub mod density_of_states;
+pub mod exact_quantum_annealing;
+pub mod observers;
+pub mod population_annealing;
+pub mod schedules;
+pub mod simulated_quantum_annealing;
+pub mod tabu_search;
+pub mod wang_landau;
 
 fn gray_code(n: SpinIndex) -> SpinIndex {
     n ^ (n >> 1)
@@ -27,11 +50,47 @@ fn from_compact_state_to_state(compact_state: CompactState) -> State {
         state[index] = true;
     }
 
-    return state;
+    state
+}
+
+/// Computes the energy of `state` in the spin glass whose interaction terms and external magnetic field are given as
+/// the `interactions` and `external_magnetic_field` arguments.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::energy;
+///
+/// let copy_gate_interactions = vec![(0, 1, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+///
+/// assert_eq!(-1.0, energy(&copy_gate_interactions, &copy_gate_external_magnetic_field, &vec![true, true]));
+/// assert_eq!(1.0, energy(&copy_gate_interactions, &copy_gate_external_magnetic_field, &vec![true, false]));
+/// ```
+pub fn energy(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    state: &State,
+) -> Energy {
+    let spin_value = |spin_index: SpinIndex| if state[spin_index] { 1.0 } else { -1.0 };
+    let interaction_energy: Energy = interactions
+        .iter()
+        .map(|(i, j, interaction_strength)| interaction_strength * spin_value(*i) * spin_value(*j))
+        .sum();
+    let external_magnetic_field_energy: Energy = external_magnetic_field
+        .iter()
+        .enumerate()
+        .map(|(i, magnetic_field_strength)| magnetic_field_strength * spin_value(i))
+        .sum();
+
+    -interaction_energy + -external_magnetic_field_energy
 }
 
 /// Finds all ground states of the spin glass whose interaction terms and external magnetic field
-/// are given as the `interactions` and `external_magnetic_field` arguments.
+/// are given as the `interactions` and `external_magnetic_field` arguments. States are grouped by their
+/// [energy_level], like [partition_function] does. A spin glass without spins, like a
+/// [crate::preprocessing::ReducedProblem] whose spins were all fixed, has a single ground state, the empty one, of
+/// energy 0.
 ///
 /// ### Example
 ///
@@ -47,23 +106,161 @@ fn from_compact_state_to_state(compact_state: CompactState) -> State {
 /// let actual_states = find_all_ground_states(&copy_gate_interactions, &copy_gate_external_magnetic_field);
 /// let expected_states = vec![(-1.0, vec![false, false]), (-1.0, vec![true, true])];
 ///
-/// assert_eq!(expected_states, actual_states)
+/// assert_eq!(expected_states, actual_states.energies_and_states())
 /// ```
 pub fn find_all_ground_states(
     interactions: &Interactions,
     external_magnetic_field: &ExternalMagneticField,
-) -> Vec<(Energy, State)> {
+) -> SampleSet {
+    let mut lowest_energy_level = i64::MAX;
+    let mut ground_states: Vec<(Energy, CompactState)> = vec![];
+    for_each_state(
+        interactions,
+        external_magnetic_field,
+        |current_energy, spins| {
+            let current_energy_level = energy_level(current_energy);
+            if current_energy_level == lowest_energy_level {
+                ground_states.push((current_energy, spins.clone()));
+            } else if current_energy_level < lowest_energy_level {
+                lowest_energy_level = current_energy_level;
+                ground_states.clear();
+                ground_states.push((current_energy, spins.clone()));
+            }
+        },
+    );
+
+    ground_states
+        .into_iter()
+        .map(|(energy, ground_state)| {
+            Sample::new(energy, from_compact_state_to_state(ground_state))
+        })
+        .collect()
+}
+
+/// Parameters for ground state counting.
+/// - `num_samples`: number of ground states to sample uniformly at random, without replacement
+/// - `seed`: rng seed that ensures the samples to be repeatable
+#[derive(Clone)]
+pub struct GroundStateCountingConfiguration {
+    pub num_samples: usize,
+    pub seed: u64,
+}
+
+impl Default for GroundStateCountingConfiguration {
+    fn default() -> Self {
+        GroundStateCountingConfiguration {
+            num_samples: 10,
+            seed: 42,
+        }
+    }
+}
+
+/// The outcome of [count_ground_states].
+/// - `energy`: the ground state energy
+/// - `count`: the number of ground states
+/// - `samples`: up to `num_samples` ground states, drawn uniformly at random
+pub struct GroundStateCount {
+    pub energy: Energy,
+    pub count: u128,
+    pub samples: SampleSet,
+}
+
+/// Counts the ground states of the spin glass whose interaction terms and external magnetic field are given as the
+/// `interactions` and `external_magnetic_field` arguments, walking every state like [find_all_ground_states] does, but
+/// without keeping all of them. Only up to `num_samples` of them are kept, as a uniform random sample of the ground
+/// states drawn with reservoir sampling, so that memory stays bounded however degenerate the spin glass is.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::{count_ground_states, GroundStateCountingConfiguration};
+///
+/// // Three spins that are free to take any value
+/// let interactions = vec![];
+/// let external_magnetic_field = vec![0.0, 0.0, 0.0];
+/// let ground_state_counting_configuration = GroundStateCountingConfiguration {
+///     num_samples: 2,
+///     ..Default::default()
+/// };
+///
+/// let ground_state_count = count_ground_states(
+///     &interactions,
+///     &external_magnetic_field,
+///     Some(&ground_state_counting_configuration),
+/// );
+///
+/// assert_eq!(0.0, ground_state_count.energy);
+/// assert_eq!(8, ground_state_count.count);
+/// assert_eq!(2, ground_state_count.samples.len());
+/// ```
+pub fn count_ground_states(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    configuration_override: Option<&GroundStateCountingConfiguration>,
+) -> GroundStateCount {
+    let config = configuration_override.cloned().unwrap_or_default();
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut lowest_energy = Energy::INFINITY;
+    let mut lowest_energy_level = i64::MAX;
+    let mut count: u128 = 0;
+    let mut reservoir: Vec<(Energy, CompactState)> = Vec::with_capacity(config.num_samples);
+    for_each_state(
+        interactions,
+        external_magnetic_field,
+        |current_energy, spins| {
+            let current_energy_level = energy_level(current_energy);
+            if current_energy_level != lowest_energy_level {
+                if current_energy_level > lowest_energy_level {
+                    return;
+                }
+                lowest_energy = current_energy;
+                lowest_energy_level = current_energy_level;
+                count = 0;
+                reservoir.clear();
+            }
+            // Every one of the ground states seen so far is in the reservoir with the same probability
+            count += 1;
+            if reservoir.len() < config.num_samples {
+                reservoir.push((current_energy, spins.clone()));
+            } else {
+                let slot = rng.gen_range(0..count);
+                if slot < config.num_samples as u128 {
+                    reservoir[slot as usize] = (current_energy, spins.clone());
+                }
+            }
+        },
+    );
+
+    GroundStateCount {
+        energy: lowest_energy,
+        count,
+        samples: reservoir
+            .into_iter()
+            .map(|(energy, ground_state)| {
+                Sample::new(energy, from_compact_state_to_state(ground_state))
+            })
+            .collect(),
+    }
+}
+
+/// Calls `visit` with the energy of every state of the spin glass, and the state itself, walking them in Gray code
+/// order so that consecutive states only differ by a single spin flip.
+fn for_each_state(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    mut visit: impl FnMut(Energy, &CompactState),
+) {
     let n = external_magnetic_field.len();
-    let initial_state = CompactState::with_capacity(n);
     let mut two_local_hamiltonian = TwoLocalHamiltonian::new(
         interactions.clone(),
         external_magnetic_field.clone(),
         Some(vec![false; n]),
     );
-
-    let initial_energy = two_local_hamiltonian.current_energy();
-    let mut lowest_energy = initial_energy;
-    let mut ground_states: Vec<(Energy, CompactState)> = vec![(initial_energy, initial_state)];
+    visit(
+        two_local_hamiltonian.current_energy(),
+        &two_local_hamiltonian.spins,
+    );
 
     for i in 1..(1 << n) {
         let prev_gray = gray_code(i - 1);
@@ -71,44 +268,195 @@ pub fn find_all_ground_states(
         if let Some(bit_pos) = bit_position_changed(prev_gray, curr_gray) {
             two_local_hamiltonian.flip_spin(bit_pos as usize);
         }
-        let current_energy = two_local_hamiltonian.current_energy();
-        if (current_energy - lowest_energy).abs() < f32::EPSILON {
-            ground_states.push((current_energy, two_local_hamiltonian.spins.clone()));
-        } else if current_energy < lowest_energy {
-            lowest_energy = current_energy;
-            ground_states.clear();
-            ground_states.push((current_energy, two_local_hamiltonian.spins.clone()));
-        }
+        visit(
+            two_local_hamiltonian.current_energy(),
+            &two_local_hamiltonian.spins,
+        );
     }
+}
 
-    ground_states
-        .into_iter()
-        .map(|(energy, ground_state)| (energy, from_compact_state_to_state(ground_state)))
-        .collect()
+/// The exact thermodynamics of a spin glass at inverse temperature `beta`, as computed by [partition_function].
+/// - `beta`: the inverse temperature
+/// - `log_partition_function`: natural logarithm of the partition function `Z = sum e^(-beta E)`
+/// - `ground_states`: every ground state, in the order in which they were found
+/// - `ground_state_probability`: Boltzmann probability of each one of the ground states, which are all equally
+///   likely, so that the probability of finding any ground state is this times their number
+/// - `density_of_states`: the number of states of every energy
+pub struct PartitionFunction {
+    pub beta: f32,
+    pub log_partition_function: f64,
+    pub ground_states: SampleSet,
+    pub ground_state_probability: f64,
+    pub density_of_states: DensityOfStates,
+}
+
+impl PartitionFunction {
+    /// Returns the Boltzmann probability of any single state of energy `energy`.
+    pub fn probability(&self, energy: Energy) -> f64 {
+        (-self.beta as f64 * energy as f64 - self.log_partition_function).exp()
+    }
+}
+
+/// Computes the partition function of the spin glass whose interaction terms and external magnetic field are given as
+/// the `interactions` and `external_magnetic_field` arguments at inverse temperature `beta`, by walking every state
+/// like [find_all_ground_states] does. Along with it, it returns the ground states, their Boltzmann probability and
+/// the density of states, which are the ground truth to validate samplers against.
+///
+/// The partition function is summed as a log-sum-exp over the energy levels, relative to the lowest one, so that it
+/// doesn't overflow at large `beta`. States are grouped into levels by their [energy_level].
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::partition_function;
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+///
+/// let partition_function = partition_function(&copy_gate_interactions, &copy_gate_external_magnetic_field, 1.0);
+///
+/// let expected_partition_function = 2.0 * 1.0_f64.exp() + 2.0 * (-1.0_f64).exp();
+/// assert!((partition_function.log_partition_function - expected_partition_function.ln()).abs() < 1e-6);
+/// assert_eq!(2, partition_function.ground_states.len());
+/// assert!((partition_function.ground_state_probability - 1.0_f64.exp() / expected_partition_function).abs() < 1e-6);
+/// assert_eq!(vec![-1.0, 1.0], partition_function.density_of_states.energies);
+/// assert_eq!(vec![2.0, 2.0], partition_function.density_of_states.degeneracies());
+/// ```
+pub fn partition_function(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    beta: f32,
+) -> PartitionFunction {
+    let mut counts: HashMap<i64, (Energy, u64)> = HashMap::new();
+    let mut lowest_energy = Energy::INFINITY;
+    let mut lowest_energy_level = i64::MAX;
+    let mut ground_states: Vec<(Energy, CompactState)> = vec![];
+    for_each_state(
+        interactions,
+        external_magnetic_field,
+        |current_energy, spins| {
+            let current_energy_level = energy_level(current_energy);
+            counts
+                .entry(current_energy_level)
+                .or_insert((current_energy, 0))
+                .1 += 1;
+            if current_energy_level == lowest_energy_level {
+                ground_states.push((current_energy, spins.clone()));
+            } else if current_energy_level < lowest_energy_level {
+                lowest_energy = current_energy;
+                lowest_energy_level = current_energy_level;
+                ground_states.clear();
+                ground_states.push((current_energy, spins.clone()));
+            }
+        },
+    );
+    let mut levels: Vec<(Energy, u64)> = counts.into_values().collect();
+    levels.sort_by(|left, right| left.0.total_cmp(&right.0));
+    let density_of_states = DensityOfStates {
+        energies: levels.iter().map(|(energy, _)| *energy).collect(),
+        log_degeneracies: levels
+            .iter()
+            .map(|(_, count)| (*count as f64).ln())
+            .collect(),
+    };
+    let log_partition_function = density_of_states.log_partition_function(beta);
+
+    PartitionFunction {
+        beta,
+        log_partition_function,
+        ground_state_probability: (-beta as f64 * lowest_energy as f64 - log_partition_function)
+            .exp(),
+        ground_states: ground_states
+            .into_iter()
+            .map(|(energy, ground_state)| {
+                Sample::new(energy, from_compact_state_to_state(ground_state))
+            })
+            .collect(),
+        density_of_states,
+    }
+}
+
+/// The order in which spins are visited during a sweep.
+/// - `Sequential`: every spin once, from the first one to the last one
+/// - `Random`: as many spins as there are in the spin glass, picked uniformly at random
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SweepOrder {
+    Sequential,
+    Random,
+}
+
+/// How a single spin flip, which would change the energy by `ΔE`, is accepted at temperature `T`.
+/// - `Metropolis`: with probability `min(1, e^(-ΔE/T))`
+/// - `HeatBath`: the spin is drawn anew from its conditional Boltzmann distribution given its neighbours, so that it
+///   points up with probability `1 / (1 + e^(-2h/T))`, where `h` is its local field
+/// - `Glauber`: with probability `1 / (1 + e^(ΔE/T))`
+///
+/// For Ising spins, heat bath and Glauber dynamics flip a spin with the same probability, and differ only in how they
+/// draw it. Unlike Metropolis, they never accept moves with certainty, not even downhill ones, which makes them
+/// better behaved when sampling at high temperature. At zero temperature, they both point the spin along its local
+/// field, and pick its value at random when it has none.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AcceptanceRule {
+    Metropolis,
+    HeatBath,
+    Glauber,
 }
 
 /// Parameters for simulated annealing.
-/// - `initial_temperature`: temperature at the zeroth step
-/// - `final_temperature`: temperature at the <sweeps> step
-/// - `sweeps`: number of sampling steps
-/// - `seed`: rng seed that ensures the whole process to be repeatable
-/// - `trace`: if true, then it will keep track of all states found on the way to the ground state
+/// - `initial_temperature`: temperature at the first sweep; if None, it's picked with [automatic_temperatures]
+/// - `final_temperature`: temperature at the last sweep; if None, it's picked with [automatic_temperatures]
+/// - `schedule`: how the temperature goes from `initial_temperature` to `final_temperature`
+/// - `sweeps`: number of sweeps of every read; a sweep attempts as many spin flips as there are spins, all at the
+///   same temperature
+/// - `sweep_order`: the order in which spins are visited during a sweep
+/// - `acceptance_rule`: how single spin flips are accepted
+/// - `num_reads`: number of independent anneals, each one starting from a random state
+/// - `num_threads`: number of threads the reads are spread across; if None, as many as the available parallelism
+/// - `seed`: rng seed that ensures the whole process to be repeatable, regardless of `num_threads`
+/// - `trace`: if true, then the trajectory of every read, with every accepted and rejected move, will be returned
+///   as an [AnnealingTrace] in the `traces` of the [SampleSet]
+/// - `observer`: if given, it is told how every read is doing at the end of each sweep, and it can stop every read at
+///   the end of the same sweep
+/// - `cluster_move`: if given, it is made after every sweep; with [ClusterMove::Houdayer], every read anneals a second
+///   replica that only serves as the partner of the moves
+/// - `logical_blocks`: if not empty, block moves over them are made after every sweep, see
+///   [crate::spin_network::SpinNetwork::logical_blocks]
+#[derive(Clone)]
 pub struct SimulatedAnnealingConfiguration {
-    pub initial_temperature: f32,
-    pub final_temperature: f32,
+    pub initial_temperature: Option<f32>,
+    pub final_temperature: Option<f32>,
+    pub schedule: Arc<dyn AnnealingSchedule>,
     pub sweeps: usize,
+    pub sweep_order: SweepOrder,
+    pub acceptance_rule: AcceptanceRule,
+    pub num_reads: usize,
+    pub num_threads: Option<usize>,
     pub seed: u64,
     pub trace: bool,
+    pub observer: Option<Arc<dyn Observer>>,
+    pub cluster_move: Option<ClusterMove>,
+    pub logical_blocks: Vec<LogicalBlock>,
 }
 
 impl Default for SimulatedAnnealingConfiguration {
     fn default() -> Self {
         SimulatedAnnealingConfiguration {
-            initial_temperature: 273.15,
-            final_temperature: 0.015,
+            initial_temperature: None,
+            final_temperature: None,
+            schedule: Arc::new(Geometric),
             sweeps: 1000,
+            sweep_order: SweepOrder::Sequential,
+            acceptance_rule: AcceptanceRule::Metropolis,
+            num_reads: 10,
+            num_threads: None,
             seed: 42,
             trace: false,
+            observer: None,
+            cluster_move: None,
+            logical_blocks: vec![],
         }
     }
 }
@@ -118,13 +466,15 @@ pub type Epoch = usize;
 /// Explores the energy landscape of the spin glass whose interaction terms and external magnetic field
 /// are given as the `interactions` and `external_magnetic_field` arguments.
 ///
-/// It will return the encountered states of lowest energy. See [SimulatedAnnealingConfiguration] for
-/// information on how to make it so that it will return every single lowest energy state found.
+/// It will run `num_reads` independent anneals, spread across threads, and return the final state of each one of
+/// them, alongside its energy and the sweep in which it was reached. Every read has its own seed, derived from the
+/// one of the configuration. See [SimulatedAnnealingConfiguration] for information on how to
+/// make it so that it will also return the trace of every read.
 ///
 /// ### Example
 ///
 /// ```
-/// use indexmap::map::VacantEntry;
+/// use std::collections::HashSet;
 /// use ernst::solvers::simulated_annealing;
 ///
 /// let s0 = 0;
@@ -133,11 +483,11 @@ pub type Epoch = usize;
 /// let copy_gate_interactions = vec![(s0, z, 1.0)];
 /// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
 ///
-/// let actual_states: Vec<_> = simulated_annealing(&copy_gate_interactions, &copy_gate_external_magnetic_field, None)
+/// let actual_states: HashSet<_> = simulated_annealing(&copy_gate_interactions, &copy_gate_external_magnetic_field, None)
 ///   .into_iter()
-///   .map(|(energy, state, _epoch)| (energy, state))
+///   .map(|sample| (sample.energy as i32, sample.state))
 ///   .collect();
-/// let expected_states = vec![(-1.0, vec![false, false]), (-1.0, vec![true, true])];
+/// let expected_states = HashSet::from([(-1, vec![false, false]), (-1, vec![true, true])]);
 ///
 /// assert_eq!(expected_states, actual_states)
 /// ```
@@ -145,103 +495,400 @@ pub fn simulated_annealing(
     interactions: &Interactions,
     external_magnetic_field: &ExternalMagneticField,
     configuration_override: Option<&SimulatedAnnealingConfiguration>,
-) -> Vec<(Energy, State, Epoch)> {
-    let mut config = SimulatedAnnealingConfiguration::default();
-    if let Some(configuration_override) = configuration_override {
-        config.initial_temperature = configuration_override.initial_temperature;
-        config.final_temperature = configuration_override.final_temperature;
-        config.sweeps = configuration_override.sweeps;
-        config.seed = configuration_override.seed;
-    }
-    let mut rng = StdRng::seed_from_u64(config.seed);
-    let initial_temperature: Temperature = OrderedFloat::from(config.initial_temperature);
-    let final_temperature: Temperature = OrderedFloat::from(config.final_temperature);
-    let one = OrderedFloat::from(1.0);
-    let cooling_rate = (final_temperature / initial_temperature).powf(one / config.sweeps as Energy);
-    let mut temperature: Temperature = initial_temperature;
-    let k = one.clone();
+) -> SampleSet {
+    let config = configuration_override.cloned().unwrap_or_default();
+    let temperatures = annealing_temperatures(
+        interactions,
+        external_magnetic_field,
+        config.initial_temperature,
+        config.final_temperature,
+        config.schedule.as_ref(),
+        config.sweeps,
+    );
 
-    let n = external_magnetic_field.len();
-    let mut two_local_hamiltonian = TwoLocalHamiltonian::new(
-        interactions.clone(),
-        external_magnetic_field.clone(),
-        Some(vec![false; n]),
+    let couplings = SparseCouplings::new(interactions, external_magnetic_field);
+    let last_sweep = AtomicUsize::new(usize::MAX);
+    let mut reads = run_seeded_jobs(
+        config.num_reads,
+        config.num_threads,
+        config.seed,
+        |read_index, rng| {
+            anneal(
+                &couplings,
+                &temperatures,
+                &config,
+                read_index,
+                rng,
+                &last_sweep,
+                config.observer.as_deref(),
+            )
+        },
     );
+    // Reads that went past the sweep an observer stopped the anneal at, before the read that stopped it got there, are
+    // rerun up to it without being observed, so that where every read ends doesn't depend on the number of threads
+    let last_sweep = last_sweep.into_inner();
+    if last_sweep != usize::MAX {
+        let rerun_reads = run_seeded_jobs(
+            config.num_reads,
+            config.num_threads,
+            config.seed,
+            |read_index, rng| {
+                let (_, _, completed_sweeps) = &reads[read_index];
+                (*completed_sweeps > last_sweep + 1).then(|| {
+                    anneal(
+                        &couplings,
+                        &temperatures,
+                        &config,
+                        read_index,
+                        rng,
+                        &AtomicUsize::new(last_sweep),
+                        None,
+                    )
+                })
+            },
+        );
+        for (read, rerun_read) in reads.iter_mut().zip(rerun_reads) {
+            if let Some(rerun_read) = rerun_read {
+                *read = rerun_read;
+            }
+        }
+    }
 
-    let initial_energy: ComparableEnergy =
-        OrderedFloat::from(two_local_hamiltonian.current_energy());
-    let mut lowest_energy: ComparableEnergy = initial_energy;
-    let mut ground_states: IndexSet<(ComparableEnergy, CompactState), ahash::RandomState> =
-        vec![(initial_energy, two_local_hamiltonian.spins.clone())]
-            .into_iter()
-            .collect();
-    let mut ground_state_update_time = vec![0];
+    let (samples, traces): (Vec<Sample>, Vec<Option<AnnealingTrace>>) = reads
+        .into_iter()
+        .map(|(sample, trace, _)| (sample, trace))
+        .unzip();
 
-    let zero = OrderedFloat::epsilon();
-    for sweep in 1..config.sweeps {
-        let spin_to_flip = rng.gen_range(0..two_local_hamiltonian.spins.len());
-        let current_energy: ComparableEnergy = two_local_hamiltonian.current_energy().into();
+    SampleSet {
+        samples,
+        traces: traces.into_iter().flatten().collect(),
+    }
+}
 
-        two_local_hamiltonian.flip_spin(spin_to_flip);
-        let new_energy: ComparableEnergy = two_local_hamiltonian.current_energy().into();
-        let delta_energy: ComparableEnergy = new_energy - current_energy;
+/// Returns the temperature of every one of `steps` steps of `schedule`, falling back to [automatic_temperatures] when
+/// the initial or final temperature is not given.
+pub(crate) fn annealing_temperatures(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    initial_temperature: Option<f32>,
+    final_temperature: Option<f32>,
+    schedule: &dyn AnnealingSchedule,
+    steps: usize,
+) -> Vec<Temperature> {
+    let (automatic_initial_temperature, automatic_final_temperature) =
+        automatic_temperatures(interactions, external_magnetic_field);
+    let initial_temperature = initial_temperature.unwrap_or(automatic_initial_temperature);
+    let final_temperature = final_temperature.unwrap_or(automatic_final_temperature);
 
-        let not_acceptance_probability = OrderedFloat::from(rng.gen::<Energy>());
-        let acceptance_probability = (-delta_energy / (k * temperature)).exp();
-        if delta_energy <= zero || acceptance_probability > not_acceptance_probability
-        {
-            let new_ground_state = (new_energy, two_local_hamiltonian.spins.clone());
-            if new_energy < lowest_energy {
-                lowest_energy = new_energy;
-                if !config.trace {
-                    ground_states.clear();
-                    ground_states.insert(new_ground_state);
-                } else {
-                    ground_states.insert(new_ground_state);
-                }
-                ground_state_update_time.push(sweep);
-            } else if (new_energy - lowest_energy).abs() <= zero {
-                if !ground_states.contains(&new_ground_state) {
-                    ground_states.insert((new_energy, two_local_hamiltonian.spins.clone()));
-                    ground_state_update_time.push(sweep);
+    (0..steps)
+        .map(|step| {
+            let progress = if steps > 1 {
+                step as f32 / (steps - 1) as f32
+            } else {
+                1.0
+            };
+            OrderedFloat::from(schedule.temperature(
+                progress,
+                initial_temperature,
+                final_temperature,
+            ))
+        })
+        .collect()
+}
+
+/// Returns how many threads `num_jobs` independent jobs should be spread across, which is `num_threads` if given and
+/// the available parallelism otherwise, but never more than there are jobs.
+pub(crate) fn thread_count(num_threads: Option<usize>, num_jobs: usize) -> usize {
+    num_threads
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|parallelism| parallelism.get())
+                .unwrap_or(1)
+        })
+        .clamp(1, num_jobs.max(1))
+}
+
+/// Derives the seed of the `read_index`-th read from the seed of the configuration with SplitMix64, so that reads are
+/// independent from each other and from the way they are spread across threads.
+pub(crate) fn read_seed(seed: u64, read_index: usize) -> u64 {
+    let mut z = seed.wrapping_add((read_index as u64 + 1).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+
+    z ^ (z >> 31)
+}
+
+/// Runs `num_jobs` independent jobs, like the reads of a solver, spread across [thread_count] threads, and returns
+/// their results in order. The `i`-th job is given `i` and an rng seeded with [read_seed] of `seed` and `i`, so that
+/// the results don't depend on the number of threads.
+pub(crate) fn run_seeded_jobs<T: Send>(
+    num_jobs: usize,
+    num_threads: Option<usize>,
+    seed: u64,
+    job: impl Fn(usize, StdRng) -> T + Sync,
+) -> Vec<T> {
+    let jobs_per_thread = num_jobs
+        .div_ceil(thread_count(num_threads, num_jobs))
+        .max(1);
+    let mut results: Vec<Option<T>> = (0..num_jobs).map(|_| None).collect();
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in results.chunks_mut(jobs_per_thread).enumerate() {
+            let job = &job;
+            scope.spawn(move || {
+                for (offset, result) in chunk.iter_mut().enumerate() {
+                    let job_index = chunk_index * jobs_per_thread + offset;
+                    let rng = StdRng::seed_from_u64(read_seed(seed, job_index));
+                    *result = Some(job(job_index, rng));
                 }
+            });
+        }
+    });
+
+    results.into_iter().flatten().collect()
+}
+
+/// Attempts as many spin flips of `spin_chain` as there are spins, in `sweep_order`, accepting them according to
+/// `acceptance_rule` at `temperature`. `on_accept` is called with the flipped spin after every accepted flip, and the
+/// number of accepted flips is returned.
+pub(crate) fn single_flip_sweep(
+    spin_chain: &mut SpinChain,
+    temperature: Temperature,
+    sweep_order: SweepOrder,
+    acceptance_rule: AcceptanceRule,
+    rng: &mut StdRng,
+    mut on_accept: impl FnMut(SpinIndex, &SpinChain),
+) -> usize {
+    let k = OrderedFloat::from(1.0);
+    let zero = OrderedFloat::epsilon();
+    let n = spin_chain.spins.len();
+    let mut accepted_flips = 0;
+    for attempt in 0..n {
+        let spin_to_flip = match sweep_order {
+            SweepOrder::Sequential => attempt,
+            SweepOrder::Random => rng.gen_range(0..n),
+        };
+
+        let delta_energy: ComparableEnergy = spin_chain.delta_energy(spin_to_flip).into();
+        let accepted = match acceptance_rule {
+            AcceptanceRule::Metropolis => {
+                delta_energy <= zero
+                    || (-delta_energy / (k * temperature)).exp()
+                        > OrderedFloat::from(rng.gen::<Energy>())
+            }
+            AcceptanceRule::HeatBath => {
+                let spin_value = if spin_chain.spins[spin_to_flip] {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let local_field = delta_energy * spin_value / 2.0;
+                let up_probability = if temperature <= OrderedFloat::from(0.0) {
+                    zero_temperature_logistic(local_field)
+                } else {
+                    OrderedFloat::from(1.0) / ((-local_field * 2.0 / (k * temperature)).exp() + 1.0)
+                };
+                let new_spin = OrderedFloat::from(rng.gen::<Energy>()) < up_probability;
+                new_spin != spin_chain.spins[spin_to_flip]
             }
-        } else {
-            two_local_hamiltonian.flip_spin(spin_to_flip);
+            AcceptanceRule::Glauber => {
+                let flip_probability = if temperature <= OrderedFloat::from(0.0) {
+                    zero_temperature_logistic(-delta_energy)
+                } else {
+                    OrderedFloat::from(1.0) / ((delta_energy / (k * temperature)).exp() + 1.0)
+                };
+                OrderedFloat::from(rng.gen::<Energy>()) < flip_probability
+            }
+        };
+        if accepted {
+            spin_chain.flip_spin(spin_to_flip);
+            accepted_flips += 1;
+            on_accept(spin_to_flip, spin_chain);
         }
+    }
 
-        temperature *= cooling_rate;
+    accepted_flips
+}
+
+/// The limit of `1 / (1 + e^(-x/T))` as `T` goes to 0, which the heat bath and Glauber probabilities turn into at zero
+/// temperature: 1 if `x` is positive, 0 if it's negative, and a coin flip if it's 0, where the formula itself is NaN.
+fn zero_temperature_logistic(x: ComparableEnergy) -> ComparableEnergy {
+    match x.into_inner() {
+        x if x > 0.0 => OrderedFloat::from(1.0),
+        x if x < 0.0 => OrderedFloat::from(0.0),
+        _ => OrderedFloat::from(0.5),
     }
+}
 
-    if !config.trace {
-        ground_state_update_time = ground_state_update_time
-            .drain(
-                (ground_state_update_time.len() - ground_states.len())
-                    ..ground_state_update_time.len(),
-            )
-            .collect()
+/// Runs the `read_index`-th read of simulated annealing, from a random state, and returns how many sweeps it went
+/// through. It stops at the end of `last_sweep`, which `observer` lowers to the sweep it asks to stop at.
+fn anneal(
+    couplings: &SparseCouplings,
+    temperatures: &[Temperature],
+    config: &SimulatedAnnealingConfiguration,
+    read_index: usize,
+    mut rng: StdRng,
+    last_sweep: &AtomicUsize,
+    observer: Option<&dyn Observer>,
+) -> (Sample, Option<AnnealingTrace>, usize) {
+    let n = couplings.len();
+
+    let initial_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let mut spin_chain = SpinChain::new(couplings, initial_state);
+    let mut partner = (config.cluster_move == Some(ClusterMove::Houdayer)).then(|| {
+        let partner_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+        SpinChain::new(couplings, partner_state)
+    });
+    let mut current_epoch = 0;
+    let mut lowest_energy: ComparableEnergy = spin_chain.current_energy().into();
+    let mut trace = config.trace.then(|| AnnealingTrace {
+        chain_id: read_index,
+        initial_state: spin_chain.spins.clone(),
+        initial_energy: couplings.energy(&spin_chain.spins),
+        sweeps: vec![],
+    });
+
+    let mut completed_sweeps = 0;
+    for (sweep, temperature) in temperatures.iter().enumerate() {
+        // Once any read is stopped at the end of a sweep, every read stops at the end of that same sweep
+        if sweep > last_sweep.load(Ordering::Relaxed) {
+            break;
+        }
+        let mut accepted_moves = vec![];
+        let mut on_accept = |flipped_spin, spin_chain: &SpinChain| {
+            current_epoch = sweep;
+            let new_energy: ComparableEnergy = spin_chain.current_energy().into();
+            lowest_energy = std::cmp::min(lowest_energy, new_energy);
+            if config.trace {
+                accepted_moves.push(AcceptedMove {
+                    spin: flipped_spin,
+                    energy: new_energy.into_inner(),
+                });
+            }
+        };
+        let accepted_flips = single_flip_sweep(
+            &mut spin_chain,
+            *temperature,
+            config.sweep_order,
+            config.acceptance_rule,
+            &mut rng,
+            &mut on_accept,
+        );
+        // A cluster move is a single move, however many spins it flips, and it counts as rejected if it flips none
+        let mut flipped_spins = vec![];
+        let mut on_flip = |flipped_spin, _: &SpinChain| flipped_spins.push(flipped_spin);
+        match config.cluster_move {
+            Some(ClusterMove::Wolff) => {
+                wolff_move(&mut spin_chain, *temperature, &mut rng, &mut on_flip);
+            }
+            Some(ClusterMove::SwendsenWang) => {
+                swendsen_wang_move(&mut spin_chain, *temperature, &mut rng, &mut on_flip);
+            }
+            Some(ClusterMove::Houdayer) => {
+                let partner = partner.as_mut().unwrap();
+                single_flip_sweep(
+                    partner,
+                    *temperature,
+                    config.sweep_order,
+                    config.acceptance_rule,
+                    &mut rng,
+                    |_, _| {},
+                );
+                houdayer_move(&mut spin_chain, partner, &mut rng, &mut on_flip);
+            }
+            None => {}
+        }
+        let mut accepted_cluster_moves = vec![];
+        let mut rejected_cluster_moves = 0;
+        let mut accepted_block_moves = vec![];
+        let mut rejected_block_moves = 0;
+        let mut on_accept_multi_spin_move =
+            |spins: &[SpinIndex], spin_chain: &SpinChain, accepted_moves: &mut Vec<_>| {
+                current_epoch = sweep;
+                let new_energy: ComparableEnergy = spin_chain.current_energy().into();
+                lowest_energy = std::cmp::min(lowest_energy, new_energy);
+                if config.trace {
+                    accepted_moves.push(AcceptedMultiSpinMove {
+                        spins: spins.to_vec(),
+                        energy: new_energy.into_inner(),
+                    });
+                }
+            };
+        if config.cluster_move.is_some() {
+            if flipped_spins.is_empty() {
+                rejected_cluster_moves += 1;
+            } else {
+                on_accept_multi_spin_move(&flipped_spins, &spin_chain, &mut accepted_cluster_moves);
+            }
+        }
+        if !config.logical_blocks.is_empty() {
+            (_, rejected_block_moves) = block_sweep(
+                &mut spin_chain,
+                &config.logical_blocks,
+                *temperature,
+                &mut rng,
+                |spins, spin_chain| {
+                    on_accept_multi_spin_move(spins, spin_chain, &mut accepted_block_moves)
+                },
+            );
+        }
+        if let Some(trace) = &mut trace {
+            trace.sweeps.push(SweepTrace {
+                sweep,
+                temperature: temperature.into_inner(),
+                accepted_moves,
+                rejected_moves: n - accepted_flips,
+                accepted_cluster_moves,
+                rejected_cluster_moves,
+                accepted_block_moves,
+                rejected_block_moves,
+            });
+        }
+        completed_sweeps = sweep + 1;
+        if let Some(observer) = observer {
+            let sweep_report = SweepReport {
+                read: read_index,
+                sweep,
+                temperature: temperature.into_inner(),
+                current_energy: spin_chain.current_energy(),
+                acceptance_rate: accepted_flips as f32 / n.max(1) as f32,
+                best_energy: lowest_energy.into_inner(),
+            };
+            if observer.observe(&sweep_report).is_break() {
+                last_sweep.fetch_min(sweep, Ordering::Relaxed);
+            }
+        }
     }
 
-    ground_states
-        .into_iter()
-        .enumerate()
-        .map(|(index, (energy, ground_state))| {
-            (
-                energy.into_inner(),
-                from_compact_state_to_state(ground_state),
-                ground_state_update_time[index],
-            )
-        })
-        .collect()
+    let sample = Sample {
+        epoch: Some(current_epoch),
+        chain_id: Some(read_index),
+        ..Sample::new(couplings.energy(&spin_chain.spins), spin_chain.spins)
+    };
+
+    (sample, trace, completed_sweeps)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::nodelib::logic_gates::XOR;
+    use crate::solvers::block_moves::LogicalBlock;
+    use crate::solvers::cluster_moves::ClusterMove;
+    use crate::solvers::energy;
+    use crate::solvers::observers::{Observer, SweepReport, TargetEnergy};
+    use crate::solvers::schedules::{Linear, LinearInBeta, Piecewise};
     use crate::solvers::{
-        find_all_ground_states, simulated_annealing, SimulatedAnnealingConfiguration,
+        count_ground_states, find_all_ground_states, partition_function, run_seeded_jobs,
+        simulated_annealing, AcceptanceRule, GroundStateCountingConfiguration,
+        SimulatedAnnealingConfiguration, SweepOrder,
     };
-    use crate::types::{ExternalMagneticField, Interactions};
-    use ahash::HashSet;
+    use crate::spin_network::SpinNetwork;
+    use crate::types::{ExternalMagneticField, Interactions, State};
+    use ahash::{HashMap, HashSet};
+    use rand::Rng;
+    use std::ops::ControlFlow;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_compute_all_states_and() {
@@ -253,7 +900,8 @@ mod tests {
         let s1_s2 = (s1, s2, -0.5);
         let interactions: Interactions = vec![s1_s2, s1_s3, s2_s3];
         let external_magnetic_field: ExternalMagneticField = vec![0.5, 0.5, -1.0];
-        let actual_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let actual_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).energies_and_states();
         let expected_states = vec![
             (-1.5, vec![false, false, false]),
             (-1.5, vec![true, false, false]),
@@ -270,7 +918,8 @@ mod tests {
         let s2 = 1;
         let interactions: Interactions = vec![(s1, s2, 1.0)];
         let external_magnetic_field: ExternalMagneticField = vec![0.0, 0.0];
-        let actual_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let actual_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).energies_and_states();
         let expected_states = vec![(-1.0, vec![false, false]), (-1.0, vec![true, true])];
 
         assert_eq!(expected_states, actual_states)
@@ -280,7 +929,8 @@ mod tests {
     fn test_compute_all_states_copy_copy_copy() {
         let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0)];
         let external_magnetic_field: ExternalMagneticField = vec![0.0, 0.0, 0.0, 0.0];
-        let actual_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let actual_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).energies_and_states();
         let expected_states = vec![
             (-3.0, vec![false, false, false, false]),
             (-3.0, vec![true, true, true, true]),
@@ -306,7 +956,8 @@ mod tests {
         interactions.push((s2_prime, s3, 1.0));
         interactions.push((s3, s3_prime, 1.0));
         let external_magnetic_field: ExternalMagneticField = vec![0.0, 0.5, 0.0, 0.5, -1.0, 0.0];
-        let actual_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let actual_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).energies_and_states();
         let expected_states = vec![
             (-4.5, vec![false, false, false, false, false, false]),
             (-4.5, vec![true, true, false, false, false, false]),
@@ -327,7 +978,8 @@ mod tests {
         let s1_s2 = (s1, s2, -0.5);
         let interactions: Interactions = vec![s1_s3, s2_s3, s1_s2];
         let external_magnetic_field: ExternalMagneticField = vec![-0.5, -0.5, 1.0];
-        let actual_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let actual_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).energies_and_states();
         let expected_states = vec![
             (-1.5, vec![false, false, false]),
             (-1.5, vec![false, true, true]),
@@ -362,7 +1014,8 @@ mod tests {
             (s3_prime, s5, 1.0),
         ];
         let external_magnetic_field: ExternalMagneticField = vec![-0.5, -0.5, 1.0, -0.5, -0.5, 1.0];
-        let actual_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let actual_states =
+            find_all_ground_states(&interactions, &external_magnetic_field).energies_and_states();
         let expected_states = vec![
             // 0, 0, 0, 0
             (-4.0, vec![false, false, false, false, false, false]),
@@ -404,19 +1057,21 @@ mod tests {
         ];
         let external_magnetic_field: ExternalMagneticField = vec![-0.5, -0.5, 1.0, -0.5, -0.5, 1.0];
         let simulated_annealing_configuration = &SimulatedAnnealingConfiguration {
-            initial_temperature: 1.0,
-            final_temperature: 0.001,
-            sweeps: 10000,
+            initial_temperature: Some(1.0),
+            final_temperature: Some(0.001),
+            sweeps: 1000,
+            num_reads: 100,
             seed: 42,
             trace: false,
+            ..Default::default()
         };
         let actual_states: HashSet<_> = simulated_annealing(
             &interactions,
             &external_magnetic_field,
-            Some(&simulated_annealing_configuration),
+            Some(simulated_annealing_configuration),
         )
         .into_iter()
-        .map(|(_energy, state, _epoch)| state)
+        .map(|sample| sample.state)
         .collect();
 
         let expected_states: HashSet<_> = vec![
@@ -434,9 +1089,563 @@ mod tests {
 
         let actual_diff = expected_states
             .difference(&actual_states)
-            .into_iter()
             .collect::<Vec<_>>();
         let expected_diff: Vec<&Vec<bool>> = vec![];
         assert_eq!(actual_diff, expected_diff);
     }
+
+    #[test]
+    fn test_simulated_annealing_with_schedules() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, -1.0), (2, 3, 1.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.5, 0.0, 0.0, 0.0];
+        let expected_state = vec![true, true, false, false];
+
+        for configuration in [
+            SimulatedAnnealingConfiguration {
+                schedule: Arc::new(LinearInBeta),
+                ..Default::default()
+            },
+            SimulatedAnnealingConfiguration {
+                schedule: Arc::new(Piecewise::new(vec![(0.0, 2.0), (0.5, 0.1)])),
+                ..Default::default()
+            },
+            SimulatedAnnealingConfiguration {
+                schedule: Arc::new(|progress: f32, _: f32, _: f32| 1.0 - 0.99 * progress),
+                ..Default::default()
+            },
+        ] {
+            let actual_states: HashSet<_> = simulated_annealing(
+                &interactions,
+                &external_magnetic_field,
+                Some(&configuration),
+            )
+            .into_iter()
+            .map(|sample| sample.state)
+            .collect();
+
+            assert_eq!(HashSet::from_iter([expected_state.clone()]), actual_states);
+        }
+    }
+
+    #[test]
+    fn test_simulated_annealing_returns_one_sample_per_read() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0), (3, 0, 1.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.0; 4];
+        let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+            sweep_order: SweepOrder::Random,
+            num_reads: 20,
+            ..Default::default()
+        };
+
+        let samples = simulated_annealing(
+            &interactions,
+            &external_magnetic_field,
+            Some(&simulated_annealing_configuration),
+        );
+
+        assert_eq!(20, samples.len());
+        for sample in samples {
+            assert_eq!(-4.0, sample.energy);
+            assert!(sample.state == vec![true; 4] || sample.state == vec![false; 4]);
+            assert!(sample.epoch.unwrap() < 1000);
+        }
+    }
+
+    #[test]
+    fn test_seeded_jobs_are_independent_of_num_threads() {
+        let results_with = |num_threads: usize| {
+            run_seeded_jobs(13, Some(num_threads), 42, |job_index, mut rng| {
+                (job_index, rng.gen::<u64>())
+            })
+        };
+
+        let single_threaded_results = results_with(1);
+        assert_eq!(
+            (0..13).collect::<Vec<_>>(),
+            single_threaded_results
+                .iter()
+                .map(|(job_index, _)| *job_index)
+                .collect::<Vec<_>>()
+        );
+        for num_threads in [2, 4, 13, 32] {
+            assert_eq!(single_threaded_results, results_with(num_threads));
+        }
+    }
+
+    #[test]
+    fn test_simulated_annealing_observer() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0), (3, 0, 1.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.0; 4];
+        let sweep_reports = Arc::new(Mutex::new(vec![]));
+        let recorder = {
+            let sweep_reports = sweep_reports.clone();
+            move |sweep_report: &SweepReport| {
+                sweep_reports.lock().unwrap().push(*sweep_report);
+                ControlFlow::Continue(())
+            }
+        };
+        let observers: Vec<Arc<dyn Observer>> =
+            vec![Arc::new(recorder), Arc::new(TargetEnergy { energy: -4.0 })];
+        let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+            sweep_order: SweepOrder::Random,
+            num_reads: 10,
+            observer: Some(Arc::new(observers)),
+            ..Default::default()
+        };
+
+        let samples = simulated_annealing(
+            &interactions,
+            &external_magnetic_field,
+            Some(&simulated_annealing_configuration),
+        );
+
+        // Every read stops at the end of the first sweep in which any read reaches the ground state
+        let sweep_reports = sweep_reports.lock().unwrap();
+        let last_sweep = sweep_reports
+            .iter()
+            .filter(|sweep_report| sweep_report.best_energy == -4.0)
+            .map(|sweep_report| sweep_report.sweep)
+            .min()
+            .unwrap();
+        assert_eq!(10, samples.len());
+        assert!(samples
+            .iter()
+            .all(|sample| sample.epoch.unwrap() <= last_sweep));
+        assert_eq!(Some(-4.0), samples.lowest_energy());
+        assert!(sweep_reports
+            .iter()
+            .all(|sweep_report| (0.0..=1.0).contains(&sweep_report.acceptance_rate)));
+    }
+
+    #[test]
+    fn test_simulated_annealing_observer_is_independent_of_num_threads() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(4, 4, CouplingDistribution::Gaussian, 7);
+        let ground_state_energy = find_all_ground_states(&interactions, &external_magnetic_field)
+            .lowest_energy()
+            .unwrap();
+        let samples_with = |num_threads: usize| {
+            let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+                num_reads: 8,
+                num_threads: Some(num_threads),
+                trace: true,
+                observer: Some(Arc::new(TargetEnergy {
+                    energy: ground_state_energy + 1e-3,
+                })),
+                ..Default::default()
+            };
+            simulated_annealing(
+                &interactions,
+                &external_magnetic_field,
+                Some(&simulated_annealing_configuration),
+            )
+        };
+
+        let single_threaded_samples = samples_with(1);
+        let completed_sweeps = single_threaded_samples.traces[0].sweeps.len();
+        assert!(completed_sweeps < 1000);
+        assert!(single_threaded_samples
+            .traces
+            .iter()
+            .all(|trace| trace.sweeps.len() == completed_sweeps));
+        for num_threads in [2, 3, 8] {
+            assert_eq!(single_threaded_samples, samples_with(num_threads));
+        }
+    }
+
+    #[test]
+    fn test_simulated_annealing_trace() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, -1.0), (2, 3, 1.0), (3, 0, 0.5)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.5, 0.0, -0.5, 0.0];
+        // Spin 0 is the only input of both blocks, so there's a single block move per sweep
+        let logical_blocks = vec![
+            LogicalBlock {
+                inputs: vec![0],
+                spins: vec![1],
+            },
+            LogicalBlock {
+                inputs: vec![1],
+                spins: vec![2, 3],
+            },
+        ];
+        let configurations = [
+            (None, vec![]),
+            (Some(ClusterMove::Wolff), vec![]),
+            (Some(ClusterMove::SwendsenWang), vec![]),
+            (Some(ClusterMove::Houdayer), vec![]),
+            (None, logical_blocks.clone()),
+            (Some(ClusterMove::Wolff), logical_blocks),
+        ];
+        for (cluster_move, logical_blocks) in configurations {
+            let moves_per_sweep =
+                4 + usize::from(cluster_move.is_some()) + usize::from(!logical_blocks.is_empty());
+            let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+                sweeps: 50,
+                sweep_order: SweepOrder::Random,
+                num_reads: 3,
+                trace: true,
+                cluster_move,
+                logical_blocks,
+                ..Default::default()
+            };
+
+            let samples = simulated_annealing(
+                &interactions,
+                &external_magnetic_field,
+                Some(&simulated_annealing_configuration),
+            );
+
+            assert_eq!(3, samples.traces.len());
+            for (sample, trace) in samples.iter().zip(&samples.traces) {
+                assert_eq!(sample.chain_id, Some(trace.chain_id));
+                assert_eq!(50, trace.sweeps.len());
+                assert_eq!(
+                    50 * moves_per_sweep,
+                    trace.num_accepted_moves() + trace.num_rejected_moves()
+                );
+                for sweep_trace in &trace.sweeps {
+                    assert_eq!(
+                        4,
+                        sweep_trace.accepted_moves.len() + sweep_trace.rejected_moves
+                    );
+                }
+                let trajectory: Vec<_> = trace.trajectory().collect();
+                for (_sweep, trace_energy, state) in &trajectory {
+                    let expected_energy = energy(&interactions, &external_magnetic_field, state);
+                    assert!((expected_energy - trace_energy).abs() < 1e-4);
+                }
+                let (last_sweep, _energy, last_state) = trajectory.last().unwrap();
+                assert_eq!(sample.epoch, Some(*last_sweep));
+                assert_eq!(&sample.state, last_state);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulated_annealing_with_cluster_moves() {
+        // Single spin flips can only remove the domain walls of a long ferromagnetic chain one random step at a time
+        let n = 100;
+        let interactions: Interactions = (0..n - 1).map(|i| (i, i + 1, 1.0)).collect();
+        let external_magnetic_field: ExternalMagneticField = vec![0.0; n];
+        for cluster_move in [
+            ClusterMove::Wolff,
+            ClusterMove::SwendsenWang,
+            ClusterMove::Houdayer,
+        ] {
+            let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+                initial_temperature: Some(2.0),
+                final_temperature: Some(0.05),
+                sweeps: 100,
+                num_reads: 4,
+                trace: true,
+                cluster_move: Some(cluster_move),
+                ..Default::default()
+            };
+
+            let samples = simulated_annealing(
+                &interactions,
+                &external_magnetic_field,
+                Some(&simulated_annealing_configuration),
+            );
+
+            for (sample, trace) in samples.iter().zip(&samples.traces) {
+                let (_sweep, _energy, last_state) = trace.trajectory().last().unwrap();
+                assert_eq!(sample.state, last_state);
+                if cluster_move != ClusterMove::Houdayer {
+                    assert_eq!(-(n as f32 - 1.0), sample.energy);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulated_annealing_with_block_moves() {
+        // The parity of four inputs, whose output is pushed towards true by its magnetic field
+        let mut spin_network = SpinNetwork::new();
+        let inputs: Vec<_> = (0..4).map(|_| spin_network.add_input_node(0.0)).collect();
+        let left_parity = spin_network.add_binary_node(inputs[0], inputs[1], &XOR::default());
+        let right_parity = spin_network.add_binary_node(inputs[2], inputs[3], &XOR::default());
+        let parity = spin_network.add_binary_node(left_parity, right_parity, &XOR::default());
+        spin_network.external_magnetic_field[parity] += 1.0;
+        let ground_state_energy = spin_network
+            .find_all_ground_states(None)
+            .lowest_energy()
+            .unwrap();
+        let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+            initial_temperature: Some(3.0),
+            final_temperature: Some(0.05),
+            sweeps: 10,
+            num_reads: 20,
+            logical_blocks: spin_network.logical_blocks(),
+            ..Default::default()
+        };
+
+        let samples = spin_network.run_simulated_annealing(
+            Some(&simulated_annealing_configuration),
+            Some(vec![inputs[0], inputs[1], inputs[2], inputs[3], parity]),
+        );
+
+        for sample in &samples {
+            assert!((sample.energy - ground_state_energy).abs() < 1e-4);
+            assert!(sample.state[4]);
+            assert_eq!(
+                1,
+                sample.state[..4].iter().filter(|spin| **spin).count() % 2
+            );
+        }
+    }
+
+    #[test]
+    fn test_acceptance_rules_sample_the_boltzmann_distribution() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (1, 2, -1.0), (2, 3, 1.0), (3, 0, 0.5)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.5, 0.0, -0.5, 0.0];
+        let temperature = 1.5;
+        let num_reads = 4000;
+        let boltzmann_weights: Vec<f32> = (0..16_usize)
+            .map(|bits| {
+                let state = (0..4).map(|i| bits >> i & 1 == 1).collect();
+                (-energy(&interactions, &external_magnetic_field, &state) / temperature).exp()
+            })
+            .collect();
+        let partition_function: f32 = boltzmann_weights.iter().sum();
+
+        for acceptance_rule in [
+            AcceptanceRule::Metropolis,
+            AcceptanceRule::HeatBath,
+            AcceptanceRule::Glauber,
+        ] {
+            // At a constant temperature, every read ends up in equilibrium
+            let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+                initial_temperature: Some(temperature),
+                final_temperature: Some(temperature),
+                sweeps: 20,
+                acceptance_rule,
+                num_reads,
+                ..Default::default()
+            };
+
+            let samples = simulated_annealing(
+                &interactions,
+                &external_magnetic_field,
+                Some(&simulated_annealing_configuration),
+            );
+
+            let mut frequencies = [0.0; 16];
+            for sample in &samples {
+                let bits: usize = (0..4).filter(|i| sample.state[*i]).map(|i| 1 << i).sum();
+                frequencies[bits] += 1.0 / num_reads as f32;
+            }
+            for (frequency, boltzmann_weight) in frequencies.iter().zip(&boltzmann_weights) {
+                assert!((frequency - boltzmann_weight / partition_function).abs() < 0.03);
+            }
+        }
+    }
+
+    #[test]
+    fn test_acceptance_rules_at_zero_temperature() {
+        // Spin 0 follows its field, and spin 1, which has none, is as likely to point up as down
+        let external_magnetic_field: ExternalMagneticField = vec![1.0, 0.0];
+        let num_reads = 1000;
+
+        for acceptance_rule in [AcceptanceRule::HeatBath, AcceptanceRule::Glauber] {
+            let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+                initial_temperature: Some(0.0),
+                final_temperature: Some(0.0),
+                schedule: Arc::new(Linear),
+                sweeps: 1,
+                acceptance_rule,
+                num_reads,
+                ..Default::default()
+            };
+
+            let samples = simulated_annealing(
+                &vec![],
+                &external_magnetic_field,
+                Some(&simulated_annealing_configuration),
+            );
+
+            assert!(samples.iter().all(|sample| sample.state[0]));
+            let up_fraction =
+                samples.iter().filter(|sample| sample.state[1]).count() as f32 / num_reads as f32;
+            assert!((up_fraction - 0.5).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_partition_function_of_a_ferromagnetic_ring() {
+        // A ring of n spins with k domain walls, k even, has energy 2k - n, and there are 2 C(n, k) such states
+        let n = 10;
+        let interactions: Interactions = (0..n).map(|i| (i, (i + 1) % n, 1.0)).collect();
+        let external_magnetic_field: ExternalMagneticField = vec![0.0; n];
+        let binomial = |k: usize| {
+            (0..k).fold(1.0, |binomial, i| {
+                binomial * (n - i) as f64 / (i + 1) as f64
+            })
+        };
+
+        let exact_thermodynamics = partition_function(&interactions, &external_magnetic_field, 0.7);
+
+        let expected_energies: Vec<f32> = (0..=n)
+            .step_by(2)
+            .map(|k| 2.0 * k as f32 - n as f32)
+            .collect();
+        let expected_degeneracies: Vec<f64> =
+            (0..=n).step_by(2).map(|k| 2.0 * binomial(k)).collect();
+        let density_of_states = &exact_thermodynamics.density_of_states;
+        assert_eq!(expected_energies, density_of_states.energies);
+        for (degeneracy, expected_degeneracy) in density_of_states
+            .degeneracies()
+            .iter()
+            .zip(&expected_degeneracies)
+        {
+            assert!((degeneracy - expected_degeneracy).abs() < 1e-6);
+        }
+        let expected_partition_function: f64 = expected_energies
+            .iter()
+            .zip(&expected_degeneracies)
+            .map(|(energy, degeneracy)| degeneracy * (-0.7 * *energy as f64).exp())
+            .sum();
+        assert!(
+            (exact_thermodynamics.log_partition_function - expected_partition_function.ln()).abs()
+                < 1e-5
+        );
+        assert_eq!(2, exact_thermodynamics.ground_states.len());
+        let total_probability: f64 = density_of_states.probabilities(0.7).iter().sum();
+        assert!((total_probability - 1.0).abs() < 1e-9);
+        assert!(
+            (exact_thermodynamics.probability(-(n as f32))
+                - exact_thermodynamics.ground_state_probability)
+                .abs()
+                < 1e-9
+        );
+
+        // At very low temperature, the ground states take all the probability, without any overflow
+        let cold_thermodynamics =
+            partition_function(&interactions, &external_magnetic_field, 1000.0);
+        assert!(
+            (cold_thermodynamics.log_partition_function - (1000.0 * n as f64 + 2.0_f64.ln())).abs()
+                < 1e-3
+        );
+        assert!((cold_thermodynamics.ground_state_probability - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ground_states_are_grouped_like_energy_levels() {
+        // A frustrated antiferromagnetic ring of odd length has 2n ground states, with one unsatisfied bond anywhere,
+        // whose energies pick up different rounding errors along the walk over all states
+        let n = 11;
+        let interactions: Interactions = (0..n).map(|i| (i, (i + 1) % n, -0.7)).collect();
+        let external_magnetic_field: ExternalMagneticField = vec![0.0; n];
+
+        let ground_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let ground_state_count = count_ground_states(&interactions, &external_magnetic_field, None);
+        let exact_thermodynamics = partition_function(&interactions, &external_magnetic_field, 1.0);
+
+        assert_eq!(2 * n, ground_states.len());
+        assert_eq!(2 * n as u128, ground_state_count.count);
+        assert_eq!(2 * n, exact_thermodynamics.ground_states.len());
+        assert!(
+            (exact_thermodynamics
+                .density_of_states
+                .ground_state_degeneracy()
+                - 2.0 * n as f64)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_find_all_ground_states_adds_up_repeated_interactions() {
+        let interactions: Interactions = vec![(0, 1, 1.0), (0, 1, 1.0)];
+        let external_magnetic_field: ExternalMagneticField = vec![0.0, 0.0];
+
+        let ground_states = find_all_ground_states(&interactions, &external_magnetic_field);
+
+        assert_eq!(Some(-2.0), ground_states.lowest_energy());
+        for ground_state in ground_states.iter() {
+            assert_eq!(
+                ground_state.energy,
+                energy(&interactions, &external_magnetic_field, &ground_state.state)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ground_states_are_only_the_lowest_energy_level() {
+        // The two lowest energies, -1.00009 and -1.00001, are closer than ENERGY_RESOLUTION, but they are different levels
+        let interactions: Interactions = vec![];
+        let external_magnetic_field: ExternalMagneticField = vec![1.00005, 0.00004];
+
+        let ground_states = find_all_ground_states(&interactions, &external_magnetic_field);
+        let ground_state_count = count_ground_states(&interactions, &external_magnetic_field, None);
+        let exact_thermodynamics = partition_function(&interactions, &external_magnetic_field, 1.0);
+
+        assert_eq!(
+            vec![vec![true, true]],
+            ground_states
+                .iter()
+                .map(|sample| sample.state.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, ground_state_count.count);
+        assert_eq!(1, exact_thermodynamics.ground_states.len());
+        assert!(
+            (exact_thermodynamics
+                .density_of_states
+                .ground_state_degeneracy()
+                - 1.0)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_count_ground_states_samples_uniformly() {
+        let interactions: Interactions = vec![
+            (0, 1, -0.5),
+            (0, 2, 1.0),
+            (1, 2, 1.0),
+            (2, 3, 1.0),
+            (3, 4, -0.5),
+            (4, 5, 1.0),
+            (3, 5, 1.0),
+        ];
+        let external_magnetic_field: ExternalMagneticField = vec![-0.5, -0.5, 1.0, -0.5, -0.5, 1.0];
+        let ground_states: HashSet<_> =
+            find_all_ground_states(&interactions, &external_magnetic_field)
+                .into_iter()
+                .map(|sample| sample.state)
+                .collect();
+
+        let ground_state_count = count_ground_states(&interactions, &external_magnetic_field, None);
+
+        assert_eq!(-4.0, ground_state_count.energy);
+        assert_eq!(8, ground_state_count.count);
+        let sampled_states: HashSet<_> = ground_state_count
+            .samples
+            .into_iter()
+            .map(|sample| sample.state)
+            .collect();
+        assert_eq!(ground_states, sampled_states);
+
+        let mut occurrences: HashMap<State, usize> = HashMap::default();
+        for seed in 0..2000 {
+            let ground_state_counting_configuration = GroundStateCountingConfiguration {
+                num_samples: 1,
+                seed,
+            };
+            let ground_state_count = count_ground_states(
+                &interactions,
+                &external_magnetic_field,
+                Some(&ground_state_counting_configuration),
+            );
+            *occurrences
+                .entry(ground_state_count.samples.samples[0].state.clone())
+                .or_default() += 1;
+        }
+        assert_eq!(8, occurrences.len());
+        for occurrence in occurrences.values() {
+            assert!((*occurrence as f32 / 2000.0 - 0.125).abs() < 0.03);
+        }
+    }
 }
@@ -0,0 +1,285 @@
+use crate::hamiltonian::SpinChain;
+use crate::types::{Energy, SpinIndex, Temperature};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+/// A LogicalBlock is a node of a [crate::spin_network::SpinNetwork], like a logic gate, seen as a function of its
+/// inputs: once `inputs` are fixed, the values of `spins`, i.e. the output, COPY and auxiliary spins that the node
+/// added, follow from the ground state of its own couplings. [crate::spin_network::SpinNetwork::logical_blocks] returns
+/// the blocks of a network in the order in which they were added, so that every block comes after the ones that feed
+/// it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogicalBlock {
+    pub inputs: Vec<SpinIndex>,
+    pub spins: Vec<SpinIndex>,
+}
+
+/// Blocks of more spins than this aren't derived by block moves, since their lowest energy values are found by trying
+/// every assignment of their spins.
+pub const MAX_BLOCK_SPINS: usize = 16;
+
+/// Attempts as many block moves of `spin_chain` as there are inputs of the whole network, i.e. spins that feed a
+/// block without being derived by another one, accepting them according to the Metropolis criterion at `temperature`.
+/// `on_accept` is called with the flipped spins after every accepted move, and the numbers of accepted and rejected
+/// moves are returned.
+///
+/// A block move flips a random one of those inputs, and then goes through every block downstream of it, in order,
+/// setting its spins to their lowest energy values given the new values of its inputs. In a circuit, it takes the
+/// network from one valid assignment to another one in a single move, instead of through a cascade of unlikely single
+/// spin flips. Blocks of more than [MAX_BLOCK_SPINS] spins, like large user-defined nodes, are left out, so their spins
+/// are only flipped by single spin moves, and the spins they output are inputs of the blocks they feed.
+///
+/// The moves break detailed balance: the move back from the new state isn't proposed with the same probability, since
+/// the spins are re-derived and degenerate assignments are picked at random, yet moves are accepted with the plain
+/// Metropolis criterion. They help annealing find ground states, but a chain that makes them doesn't sample the
+/// Boltzmann distribution.
+pub(crate) fn block_sweep(
+    spin_chain: &mut SpinChain,
+    blocks: &[LogicalBlock],
+    temperature: Temperature,
+    rng: &mut StdRng,
+    mut on_accept: impl FnMut(&[SpinIndex], &SpinChain),
+) -> (usize, usize) {
+    let blocks: Vec<&LogicalBlock> = blocks
+        .iter()
+        .filter(|block| block.spins.len() <= MAX_BLOCK_SPINS)
+        .collect();
+    // Spins that some block derives are never flipped on their own, since that would leave their block inconsistent
+    let derived_spins: HashSet<SpinIndex> = blocks
+        .iter()
+        .flat_map(|block| block.spins.iter().copied())
+        .collect();
+    let mut roots: Vec<SpinIndex> = blocks
+        .iter()
+        .flat_map(|block| block.inputs.iter().copied())
+        .filter(|input| !derived_spins.contains(input))
+        .collect();
+    roots.sort_unstable();
+    roots.dedup();
+
+    let mut accepted_moves = 0;
+    for _ in 0..roots.len() {
+        let root = roots[rng.gen_range(0..roots.len())];
+        let flips = propose_block_move(spin_chain, &blocks, root, rng);
+
+        // Flipping a set of spins changes the energy by the sum of their single flip changes, minus twice the
+        // contribution of the couplings between them, which the single flip changes count as broken twice
+        let mut delta_energy: Energy = 0.0;
+        for spin in &flips {
+            delta_energy += spin_chain.delta_energy(*spin);
+            let spin_value = if spin_chain.spins[*spin] { 1.0 } else { -1.0 };
+            for (neighbour, interaction_strength) in &spin_chain.couplings().neighbours[*spin] {
+                if flips.contains(neighbour) {
+                    let neighbour_value = if spin_chain.spins[*neighbour] {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    delta_energy -= 2.0 * interaction_strength * spin_value * neighbour_value;
+                }
+            }
+        }
+        if delta_energy <= 0.0
+            || (-delta_energy / temperature.into_inner()).exp() > rng.gen::<Energy>()
+        {
+            for spin in &flips {
+                spin_chain.flip_spin(*spin);
+            }
+            on_accept(&flips, spin_chain);
+            accepted_moves += 1;
+        }
+    }
+
+    (accepted_moves, roots.len() - accepted_moves)
+}
+
+/// Returns the spins that a block move from `root` would flip, without flipping them.
+fn propose_block_move(
+    spin_chain: &SpinChain,
+    blocks: &[&LogicalBlock],
+    root: SpinIndex,
+    rng: &mut StdRng,
+) -> Vec<SpinIndex> {
+    let neighbours = &spin_chain.couplings().neighbours;
+    let external_magnetic_field = &spin_chain.couplings().external_magnetic_field;
+    let mut proposed_values: HashMap<SpinIndex, bool> =
+        HashMap::from([(root, !spin_chain.spins[root])]);
+
+    for block in blocks {
+        if !block
+            .inputs
+            .iter()
+            .any(|input| proposed_values.contains_key(input))
+        {
+            continue;
+        }
+        let value_of = |spin: SpinIndex, assignment: usize| -> f32 {
+            let value = match block
+                .spins
+                .iter()
+                .position(|block_spin| *block_spin == spin)
+            {
+                Some(position) => assignment >> position & 1 == 1,
+                None => *proposed_values
+                    .get(&spin)
+                    .unwrap_or(&spin_chain.spins[spin]),
+            };
+            if value {
+                1.0
+            } else {
+                -1.0
+            }
+        };
+        // Only the terms of the block itself, i.e. its fields and its couplings to itself and to its inputs, are
+        // minimized, since the spins downstream of it are yet to be derived
+        let block_energy = |assignment: usize| -> Energy {
+            let mut energy = 0.0;
+            for (position, spin) in block.spins.iter().enumerate() {
+                energy -= external_magnetic_field[*spin] * value_of(*spin, assignment);
+                for (neighbour, interaction_strength) in &neighbours[*spin] {
+                    let is_earlier_block_spin = block.spins[..position].contains(neighbour);
+                    if is_earlier_block_spin || block.inputs.contains(neighbour) {
+                        energy -= interaction_strength
+                            * value_of(*spin, assignment)
+                            * value_of(*neighbour, assignment);
+                    }
+                }
+            }
+
+            energy
+        };
+
+        let mut best_assignment = 0;
+        let mut lowest_energy = Energy::INFINITY;
+        let mut ties = 0;
+        for assignment in 0..1_usize << block.spins.len() {
+            let energy = block_energy(assignment);
+            if energy < lowest_energy {
+                best_assignment = assignment;
+                lowest_energy = energy;
+                ties = 1;
+            } else if energy == lowest_energy {
+                // Degenerate assignments, like the auxiliary spins of some gates, are picked uniformly at random
+                ties += 1;
+                if rng.gen_range(0..ties) == 0 {
+                    best_assignment = assignment;
+                }
+            }
+        }
+        for (position, spin) in block.spins.iter().enumerate() {
+            proposed_values.insert(*spin, best_assignment >> position & 1 == 1);
+        }
+    }
+
+    let mut flips: Vec<SpinIndex> = proposed_values
+        .into_iter()
+        .filter(|(spin, value)| spin_chain.spins[*spin] != *value)
+        .map(|(spin, _)| spin)
+        .collect();
+    flips.sort_unstable();
+
+    flips
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hamiltonian::{SparseCouplings, SpinChain};
+    use crate::nodelib::logic_gates::{AND, NOT, XOR};
+    use crate::solvers::block_moves::{block_sweep, MAX_BLOCK_SPINS};
+    use crate::solvers::{simulated_annealing, SimulatedAnnealingConfiguration};
+    use crate::spin_network::SpinNetwork;
+    use crate::types::{Node, SpinIndex, UnaryNode};
+    use ordered_float::OrderedFloat;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// A user-defined node that negates its input through a chain of NOT gates, which all end up in its block.
+    struct NotChain {
+        length: usize,
+    }
+    impl Node for NotChain {
+        fn connect(&self, spin_network: &mut SpinNetwork) -> SpinIndex {
+            spin_network.add_output_node(0.0)
+        }
+    }
+    impl UnaryNode for NotChain {
+        fn connect_to_one(&self, spin_network: &mut SpinNetwork, input: SpinIndex) -> SpinIndex {
+            (0..self.length).fold(input, |spin, _| {
+                spin_network.add_unary_node(spin, &NOT::default())
+            })
+        }
+    }
+
+    #[test]
+    fn test_block_moves_skip_large_blocks() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(1.0);
+        let s1 = spin_network.add_input_node(1.0);
+        let z_chain = spin_network.add_unary_node(s0, &NotChain { length: 20 });
+        let z = spin_network.add_binary_node(z_chain, s1, &AND::default());
+        let blocks = spin_network.logical_blocks();
+        assert!(blocks[0].spins.len() > MAX_BLOCK_SPINS);
+        let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+            sweeps: 2000,
+            logical_blocks: blocks,
+            ..Default::default()
+        };
+
+        let samples = simulated_annealing(
+            &spin_network.interactions,
+            &spin_network.external_magnetic_field,
+            Some(&simulated_annealing_configuration),
+        );
+
+        let ground_state = &samples.lowest().samples[0].state;
+        assert_eq!(
+            vec![true, true, true, true],
+            vec![
+                ground_state[s0],
+                ground_state[s1],
+                ground_state[z_chain],
+                ground_state[z]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_moves_keep_circuits_valid() {
+        // Every valid assignment of a circuit without input fields is a ground state, so every move is accepted, even
+        // at a temperature at which single spin flips never are
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        let s2 = spin_network.add_input_node(0.0);
+        let z_aux = spin_network.add_binary_node(s0, s1, &XOR::default());
+        let z = spin_network.add_binary_node(z_aux, s2, &AND::default());
+        let couplings = SparseCouplings::new(
+            &spin_network.interactions,
+            &spin_network.external_magnetic_field,
+        );
+        let ground_state = spin_network.find_all_ground_states(None).samples[0].clone();
+        let mut spin_chain = SpinChain::new(&couplings, ground_state.state);
+        let mut rng = StdRng::seed_from_u64(7);
+        let blocks = spin_network.logical_blocks();
+
+        let mut accepted_moves = 0;
+        for _ in 0..20 {
+            let (accepted, rejected) = block_sweep(
+                &mut spin_chain,
+                &blocks,
+                OrderedFloat::from(0.01),
+                &mut rng,
+                |_, _| {},
+            );
+            accepted_moves += accepted;
+            assert_eq!(0, rejected);
+
+            let spins = &spin_chain.spins;
+            assert_eq!(ground_state.energy, spin_chain.current_energy());
+            assert_eq!(spins[z_aux], spins[s0] ^ spins[s1]);
+            assert_eq!(spins[z], spins[z_aux] && spins[s2]);
+        }
+        assert_eq!(20 * 3, accepted_moves);
+    }
+}
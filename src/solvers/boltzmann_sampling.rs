@@ -0,0 +1,190 @@
+use crate::hamiltonian::{SparseCouplings, SpinChain};
+use crate::sample_set::{Sample, SampleSet};
+use crate::solvers::{run_seeded_jobs, single_flip_sweep, AcceptanceRule, SweepOrder};
+use crate::types::{ExternalMagneticField, Interactions, State};
+use ordered_float::OrderedFloat;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// Parameters for Boltzmann sampling.
+/// - `num_samples`: number of samples, spread as evenly as possible across the chains
+/// - `burn_in`: number of sweeps every chain goes through before its first sample, so that it forgets its random
+///   initial state
+/// - `thinning`: number of sweeps between consecutive samples of a chain, so that they are less correlated
+/// - `num_chains`: number of independent Markov chains, each one starting from a random state
+/// - `sweep_order`: the order in which spins are visited during a sweep
+/// - `acceptance_rule`: how single spin flips are accepted
+/// - `num_threads`, `seed`: how the chains are spread across threads and seeded, like the reads of
+///   [crate::solvers::SimulatedAnnealingConfiguration]
+#[derive(Clone)]
+pub struct BoltzmannSamplingConfiguration {
+    pub num_samples: usize,
+    pub burn_in: usize,
+    pub thinning: usize,
+    pub num_chains: usize,
+    pub sweep_order: SweepOrder,
+    pub acceptance_rule: AcceptanceRule,
+    pub num_threads: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for BoltzmannSamplingConfiguration {
+    fn default() -> Self {
+        BoltzmannSamplingConfiguration {
+            num_samples: 1000,
+            burn_in: 1000,
+            thinning: 10,
+            num_chains: 10,
+            sweep_order: SweepOrder::Sequential,
+            acceptance_rule: AcceptanceRule::Metropolis,
+            num_threads: None,
+            seed: 42,
+        }
+    }
+}
+
+/// Draws samples from the Boltzmann distribution at inverse temperature `beta` of the spin glass whose interaction
+/// terms and external magnetic field are given as the `interactions` and `external_magnetic_field` arguments, with
+/// Markov chain Monte Carlo.
+///
+/// Every chain is kept at `beta` from the start, goes through `burn_in` sweeps, and then yields a sample every
+/// `thinning` sweeps. Every sample has the chain it comes from as `chain_id`, and the sweep it was taken at as `epoch`,
+/// so that the samples of a chain can be told apart when estimating autocorrelations. See [crate::observables] for
+/// estimators of thermodynamic quantities over the samples.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::boltzmann_sampling::sample_boltzmann;
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+///
+/// let samples = sample_boltzmann(&copy_gate_interactions, &copy_gate_external_magnetic_field, 1.0, None);
+///
+/// // Aligned spins are e^2 times as likely as anti-aligned ones
+/// let aligned_fraction = samples.iter().filter(|sample| sample.state[s0] == sample.state[z]).count() as f32
+///     / samples.len() as f32;
+/// let expected_aligned_fraction = 1.0 / (1.0 + (-2.0_f32).exp());
+/// assert_eq!(1000, samples.len());
+/// assert!((aligned_fraction - expected_aligned_fraction).abs() < 0.05);
+/// ```
+pub fn sample_boltzmann(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    beta: f32,
+    configuration_override: Option<&BoltzmannSamplingConfiguration>,
+) -> SampleSet {
+    let config = configuration_override.cloned().unwrap_or_default();
+    assert!(beta > 0.0, "The inverse temperature has to be positive");
+    assert!(
+        config.num_chains > 0,
+        "Boltzmann sampling needs at least one chain"
+    );
+    assert!(
+        config.thinning > 0,
+        "The thinning has to be at least one sweep"
+    );
+
+    let couplings = SparseCouplings::new(interactions, external_magnetic_field);
+
+    run_seeded_jobs(
+        config.num_chains,
+        config.num_threads,
+        config.seed,
+        |chain_index, rng| run_chain(&couplings, beta, &config, chain_index, rng),
+    )
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Runs the `chain_index`-th chain of Boltzmann sampling, from a random state, and returns its samples.
+fn run_chain(
+    couplings: &SparseCouplings,
+    beta: f32,
+    config: &BoltzmannSamplingConfiguration,
+    chain_index: usize,
+    mut rng: StdRng,
+) -> Vec<Sample> {
+    let n = couplings.len();
+    let temperature = OrderedFloat::from(1.0 / beta);
+    // The first chains take one more sample each, when they can't all take the same number of them
+    let num_samples = config.num_samples / config.num_chains
+        + usize::from(chain_index < config.num_samples % config.num_chains);
+
+    let initial_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let mut spin_chain = SpinChain::new(couplings, initial_state);
+    let mut sweep = 0;
+    let mut samples = Vec::with_capacity(num_samples);
+    while samples.len() < num_samples {
+        single_flip_sweep(
+            &mut spin_chain,
+            temperature,
+            config.sweep_order,
+            config.acceptance_rule,
+            &mut rng,
+            |_, _| {},
+        );
+        sweep += 1;
+        if sweep >= config.burn_in && (sweep - config.burn_in) % config.thinning == 0 {
+            samples.push(Sample {
+                epoch: Some(sweep),
+                chain_id: Some(chain_index),
+                ..Sample::new(
+                    couplings.energy(&spin_chain.spins),
+                    spin_chain.spins.clone(),
+                )
+            });
+        }
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::boltzmann_sampling::{sample_boltzmann, BoltzmannSamplingConfiguration};
+
+    #[test]
+    fn test_sample_boltzmann_chains_and_epochs() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(3, 3, CouplingDistribution::Gaussian, 7);
+        let boltzmann_sampling_configuration = BoltzmannSamplingConfiguration {
+            num_samples: 23,
+            burn_in: 5,
+            thinning: 3,
+            num_chains: 4,
+            ..Default::default()
+        };
+
+        let samples = sample_boltzmann(
+            &interactions,
+            &external_magnetic_field,
+            0.5,
+            Some(&boltzmann_sampling_configuration),
+        );
+
+        assert_eq!(23, samples.len());
+        let chain_lengths: Vec<usize> = (0..4)
+            .map(|chain| {
+                samples
+                    .iter()
+                    .filter(|sample| sample.chain_id == Some(chain))
+                    .count()
+            })
+            .collect();
+        assert_eq!(vec![6, 6, 6, 5], chain_lengths);
+        let first_chain_epochs: Vec<_> = samples
+            .iter()
+            .take(6)
+            .map(|sample| sample.epoch.unwrap())
+            .collect();
+        assert_eq!(vec![5, 8, 11, 14, 17, 20], first_chain_epochs);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::hamiltonian::SpinChain;
+use crate::types::{SpinIndex, Temperature};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// A ClusterMove flips many spins at once, which lets annealing solvers escape configurations that single spin flips
+/// can only leave through high energy states, like domains of a long chain of ferromagnetic couplings. Annealing
+/// solvers make one of them after every sweep.
+/// - `Wolff`: grows a single cluster from a random spin, adding every neighbour whose coupling is satisfied with
+///   probability `1 - e^(-2|J|/T)`, and flips it
+/// - `SwendsenWang`: splits all spins into clusters in the same way, and flips every one of them independently
+/// - `Houdayer`: needs a pair of replicas, and flips, in both of them, a cluster of spins in which they disagree.
+///   Since it doesn't change their total energy, it is always accepted, and annealing solvers pair replicas up for it
+///
+/// Both Wolff and Swendsen-Wang clusters are grown on satisfied couplings of either sign, and the change of energy
+/// that the external magnetic field makes is accounted for when deciding whether to flip them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClusterMove {
+    Wolff,
+    SwendsenWang,
+    Houdayer,
+}
+
+/// Returns whether the coupling between spins `i` and `j` of `spin_chain` joins them in a cluster at `temperature`.
+fn is_bonded(
+    spin_chain: &SpinChain,
+    i: SpinIndex,
+    j: SpinIndex,
+    interaction_strength: f32,
+    temperature: Temperature,
+    rng: &mut StdRng,
+) -> bool {
+    let satisfied = (interaction_strength > 0.0) == (spin_chain.spins[i] == spin_chain.spins[j]);
+    let bond_probability =
+        1.0 - (-2.0 * interaction_strength.abs() / temperature.into_inner()).exp();
+
+    interaction_strength != 0.0 && satisfied && rng.gen::<f32>() < bond_probability
+}
+
+/// Returns by how much the external magnetic field energy would change if all spins of `cluster` were flipped.
+fn field_delta_energy(spin_chain: &SpinChain, cluster: &[SpinIndex]) -> f32 {
+    let external_magnetic_field = &spin_chain.couplings().external_magnetic_field;
+    cluster
+        .iter()
+        .map(|spin| {
+            let spin_value = if spin_chain.spins[*spin] { 1.0 } else { -1.0 };
+            2.0 * external_magnetic_field[*spin] * spin_value
+        })
+        .sum()
+}
+
+/// Grows a cluster from `seed` on the couplings that [is_bonded] picks, skipping the spins already in `in_cluster`.
+fn grow_cluster(
+    spin_chain: &SpinChain,
+    seed: SpinIndex,
+    in_cluster: &mut [bool],
+    temperature: Temperature,
+    rng: &mut StdRng,
+) -> Vec<SpinIndex> {
+    let neighbours = &spin_chain.couplings().neighbours;
+    in_cluster[seed] = true;
+    let mut cluster = vec![seed];
+    let mut frontier = 0;
+    while frontier < cluster.len() {
+        let spin = cluster[frontier];
+        frontier += 1;
+        for (neighbour, interaction_strength) in &neighbours[spin] {
+            if !in_cluster[*neighbour]
+                && is_bonded(
+                    spin_chain,
+                    spin,
+                    *neighbour,
+                    *interaction_strength,
+                    temperature,
+                    rng,
+                )
+            {
+                in_cluster[*neighbour] = true;
+                cluster.push(*neighbour);
+            }
+        }
+    }
+
+    cluster
+}
+
+/// Makes a Wolff move on `spin_chain` at `temperature`. The cluster is flipped with the Metropolis probability of the
+/// change in external magnetic field energy. `on_flip` is called after every flipped spin, and the number of flipped
+/// spins is returned.
+pub(crate) fn wolff_move(
+    spin_chain: &mut SpinChain,
+    temperature: Temperature,
+    rng: &mut StdRng,
+    mut on_flip: impl FnMut(SpinIndex, &SpinChain),
+) -> usize {
+    let n = spin_chain.spins.len();
+    if n == 0 {
+        return 0;
+    }
+    let seed = rng.gen_range(0..n);
+    let mut in_cluster = vec![false; n];
+    let cluster = grow_cluster(spin_chain, seed, &mut in_cluster, temperature, rng);
+
+    let delta_energy = field_delta_energy(spin_chain, &cluster);
+    if delta_energy > 0.0 && (-delta_energy / temperature.into_inner()).exp() <= rng.gen::<f32>() {
+        return 0;
+    }
+    for spin in &cluster {
+        spin_chain.flip_spin(*spin);
+        on_flip(*spin, spin_chain);
+    }
+
+    cluster.len()
+}
+
+/// Makes a Swendsen-Wang move on `spin_chain` at `temperature`. Every cluster is flipped with the heat bath
+/// probability of the change in external magnetic field energy, which is one half without a field. `on_flip` is called
+/// after every flipped spin, and the number of flipped spins is returned.
+pub(crate) fn swendsen_wang_move(
+    spin_chain: &mut SpinChain,
+    temperature: Temperature,
+    rng: &mut StdRng,
+    mut on_flip: impl FnMut(SpinIndex, &SpinChain),
+) -> usize {
+    let n = spin_chain.spins.len();
+    let mut in_cluster = vec![false; n];
+    let mut clusters = vec![];
+    for seed in 0..n {
+        if !in_cluster[seed] {
+            clusters.push(grow_cluster(
+                spin_chain,
+                seed,
+                &mut in_cluster,
+                temperature,
+                rng,
+            ));
+        }
+    }
+
+    let mut flipped_spins = 0;
+    for cluster in clusters {
+        let delta_energy = field_delta_energy(spin_chain, &cluster);
+        let flip_probability = 1.0 / (1.0 + (delta_energy / temperature.into_inner()).exp());
+        if rng.gen::<f32>() < flip_probability {
+            for spin in &cluster {
+                spin_chain.flip_spin(*spin);
+                on_flip(*spin, spin_chain);
+            }
+            flipped_spins += cluster.len();
+        }
+    }
+
+    flipped_spins
+}
+
+/// Makes a Houdayer move on the pair of replicas `first` and `second`: a cluster of connected spins in which they
+/// disagree is grown from a random one of them, and flipped in both replicas. `on_flip` is called after every flipped
+/// spin of `first`, and the number of flipped spins of each replica is returned.
+pub(crate) fn houdayer_move(
+    first: &mut SpinChain,
+    second: &mut SpinChain,
+    rng: &mut StdRng,
+    mut on_flip: impl FnMut(SpinIndex, &SpinChain),
+) -> usize {
+    let disagreements: Vec<SpinIndex> = (0..first.spins.len())
+        .filter(|spin| first.spins[*spin] != second.spins[*spin])
+        .collect();
+    if disagreements.is_empty() {
+        return 0;
+    }
+    let neighbours = &first.couplings().neighbours;
+    let seed = disagreements[rng.gen_range(0..disagreements.len())];
+    let mut in_cluster = vec![false; first.spins.len()];
+    in_cluster[seed] = true;
+    let mut cluster = vec![seed];
+    let mut frontier = 0;
+    while frontier < cluster.len() {
+        let spin = cluster[frontier];
+        frontier += 1;
+        for (neighbour, interaction_strength) in &neighbours[spin] {
+            if *interaction_strength != 0.0
+                && !in_cluster[*neighbour]
+                && first.spins[*neighbour] != second.spins[*neighbour]
+            {
+                in_cluster[*neighbour] = true;
+                cluster.push(*neighbour);
+            }
+        }
+    }
+
+    for spin in &cluster {
+        first.flip_spin(*spin);
+        second.flip_spin(*spin);
+        on_flip(*spin, first);
+    }
+
+    cluster.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::hamiltonian::{SparseCouplings, SpinChain};
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::cluster_moves::{houdayer_move, swendsen_wang_move, wolff_move};
+    use crate::types::State;
+    use ordered_float::OrderedFloat;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_houdayer_move_conserves_total_energy() {
+        let (interactions, mut external_magnetic_field) =
+            edwards_anderson_2d(6, 6, CouplingDistribution::Gaussian, 7);
+        external_magnetic_field[3] = 0.7;
+        let couplings = SparseCouplings::new(&interactions, &external_magnetic_field);
+        let mut rng = StdRng::seed_from_u64(7);
+        let random_state =
+            |rng: &mut StdRng| -> State { (0..36).map(|_| rng.gen::<bool>()).collect() };
+        let mut first = SpinChain::new(&couplings, random_state(&mut rng));
+        let mut second = SpinChain::new(&couplings, random_state(&mut rng));
+
+        for _ in 0..20 {
+            let total_energy = first.current_energy() + second.current_energy();
+            let overlap_before: usize = (0..36)
+                .filter(|spin| first.spins[*spin] == second.spins[*spin])
+                .count();
+
+            houdayer_move(&mut first, &mut second, &mut rng, |_, _| {});
+
+            let overlap_after: usize = (0..36)
+                .filter(|spin| first.spins[*spin] == second.spins[*spin])
+                .count();
+            assert!((first.current_energy() + second.current_energy() - total_energy).abs() < 1e-4);
+            assert_eq!(overlap_before, overlap_after);
+        }
+    }
+
+    #[test]
+    fn test_cluster_moves_flip_whole_ferromagnetic_domains() {
+        // At low temperature, a ferromagnetic chain with a single domain wall is split into its two domains
+        let interactions = (0..9).map(|i| (i, i + 1, 1.0)).collect();
+        let couplings = SparseCouplings::new(&interactions, &vec![0.0; 10]);
+        let state: State = (0..10).map(|i| i < 4).collect();
+        let mut rng = StdRng::seed_from_u64(7);
+        let low_temperature = OrderedFloat::from(0.01);
+
+        let mut spin_chain = SpinChain::new(&couplings, state.clone());
+        let flipped_spins = wolff_move(&mut spin_chain, low_temperature, &mut rng, |_, _| {});
+        assert!(flipped_spins == 4 || flipped_spins == 6);
+        assert_eq!(spin_chain.current_energy(), -9.0);
+
+        let mut spin_chain = SpinChain::new(&couplings, state);
+        for _ in 0..10 {
+            swendsen_wang_move(&mut spin_chain, low_temperature, &mut rng, |_, _| {});
+            assert!(spin_chain.current_energy() <= -7.0);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::types::Energy;
+
+/// The density of states of a spin glass, i.e. how many states there are of every energy, from which its
+/// thermodynamics at any temperature follow.
+/// - `energies`: the energy levels, in increasing order
+/// - `log_degeneracies`: natural logarithm of the number of states of every energy level, which is kept as a logarithm
+///   because it grows exponentially with the number of spins
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityOfStates {
+    pub energies: Vec<Energy>,
+    pub log_degeneracies: Vec<f64>,
+}
+
+impl DensityOfStates {
+    /// Returns the number of states of every energy level.
+    pub fn degeneracies(&self) -> Vec<f64> {
+        self.log_degeneracies
+            .iter()
+            .map(|log_degeneracy| log_degeneracy.exp())
+            .collect()
+    }
+    /// Returns the number of states of the lowest energy level, i.e. the degeneracy of the ground state.
+    pub fn ground_state_degeneracy(&self) -> f64 {
+        self.log_degeneracies
+            .first()
+            .map_or(0.0, |log_degeneracy| log_degeneracy.exp())
+    }
+    /// Returns the natural logarithm of the partition function at inverse temperature `beta`. It's summed as a
+    /// log-sum-exp, relative to the largest term, so that it never overflows.
+    pub fn log_partition_function(&self, beta: f32) -> f64 {
+        let log_weights = self.log_weights(beta);
+        let largest_log_weight = log_weights
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if largest_log_weight == f64::NEG_INFINITY {
+            return largest_log_weight;
+        }
+
+        largest_log_weight
+            + log_weights
+                .iter()
+                .map(|log_weight| (log_weight - largest_log_weight).exp())
+                .sum::<f64>()
+                .ln()
+    }
+    /// Returns the Boltzmann probability of every energy level at inverse temperature `beta`, i.e. the probability of
+    /// finding the spin glass in any state of that energy.
+    pub fn probabilities(&self, beta: f32) -> Vec<f64> {
+        let log_partition_function = self.log_partition_function(beta);
+        self.log_weights(beta)
+            .iter()
+            .map(|log_weight| (log_weight - log_partition_function).exp())
+            .collect()
+    }
+    /// Returns the mean energy at inverse temperature `beta`.
+    pub fn mean_energy(&self, beta: f32) -> Energy {
+        self.probabilities(beta)
+            .iter()
+            .zip(&self.energies)
+            .map(|(probability, energy)| probability * *energy as f64)
+            .sum::<f64>() as Energy
+    }
+    /// Returns the specific heat at inverse temperature `beta`, i.e. `beta^2 (<E^2> - <E>^2)`.
+    pub fn specific_heat(&self, beta: f32) -> f32 {
+        let probabilities = self.probabilities(beta);
+        let moment = |power: i32| -> f64 {
+            probabilities
+                .iter()
+                .zip(&self.energies)
+                .map(|(probability, energy)| probability * (*energy as f64).powi(power))
+                .sum()
+        };
+
+        ((beta as f64).powi(2) * (moment(2) - moment(1).powi(2))) as f32
+    }
+    /// Returns the logarithm of the Boltzmann weight of every energy level, including its degeneracy.
+    fn log_weights(&self, beta: f32) -> Vec<f64> {
+        self.energies
+            .iter()
+            .zip(&self.log_degeneracies)
+            .map(|(energy, log_degeneracy)| log_degeneracy - beta as f64 * *energy as f64)
+            .collect()
+    }
+}
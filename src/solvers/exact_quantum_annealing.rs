@@ -0,0 +1,549 @@
+use crate::hamiltonian::{SparseCouplings, SpinChain};
+use crate::sample_set::is_ground_state_energy;
+use crate::solvers::simulated_quantum_annealing::AnnealingFunction;
+use crate::types::{ExternalMagneticField, Interactions};
+use std::sync::Arc;
+
+/// Parameters for the exact simulation of quantum annealing.
+/// - `transverse_field`: A(s), the strength of the transverse field
+/// - `problem_scale`: B(s), the scale of the spin glass
+/// - `annealing_time`: how long the anneal lasts, in units of ħ over the units of A(s) and B(s)
+/// - `time_steps`: number of steps the time evolution is split into
+/// - `gap_samples`: number of evenly spaced points of the annealing path at which the spectral gap is computed; if 0,
+///   the spectral gap is not computed
+/// - `lanczos_iterations`: number of Lanczos iterations used to find every eigenvalue
+///
+/// Every time step and every Lanczos iteration goes through each of the `2^n` amplitudes once per spin. The spectral
+/// gaps take about `3 * gap_samples * lanczos_iterations` Lanczos iterations, 15000 by default, which amounts to
+/// minutes on 20 spins, so that's where lowering `gap_samples` saves the most.
+#[derive(Clone)]
+pub struct ExactQuantumAnnealingConfiguration {
+    pub transverse_field: Arc<dyn AnnealingFunction>,
+    pub problem_scale: Arc<dyn AnnealingFunction>,
+    pub annealing_time: f32,
+    pub time_steps: usize,
+    pub gap_samples: usize,
+    pub lanczos_iterations: usize,
+}
+
+impl Default for ExactQuantumAnnealingConfiguration {
+    fn default() -> Self {
+        ExactQuantumAnnealingConfiguration {
+            transverse_field: Arc::new(|s: f32| 3.0 * (1.0 - s)),
+            problem_scale: Arc::new(|s: f32| s),
+            annealing_time: 10.0,
+            time_steps: 1000,
+            gap_samples: 50,
+            lanczos_iterations: 100,
+        }
+    }
+}
+
+/// The outcome of [exact_quantum_annealing].
+/// - `ground_state_probability`: probability of measuring any ground state of the spin glass at the end of the anneal
+/// - `probabilities`: probability of measuring every state at the end of the anneal; bit `i` of the index of a state
+///   is the value of its `i`-th spin
+/// - `spectral_gaps`: the spectral gap at every sampled point `s` of the annealing path
+/// - `minimum_gap`: the smallest of the spectral gaps, alongside the point at which it was found
+pub struct QuantumEvolution {
+    pub ground_state_probability: f32,
+    pub probabilities: Vec<f32>,
+    pub spectral_gaps: Vec<(f32, f32)>,
+    pub minimum_gap: (f32, f32),
+}
+
+/// Simulates quantum annealing of the spin glass whose interaction terms and external magnetic field are given as the
+/// `interactions` and `external_magnetic_field` arguments exactly, by evolving the state vector under the transverse
+/// field Ising Hamiltonian `H(s) = -A(s) Σ σˣ_i + B(s) H_problem`, with `s = t / annealing_time`. `H_problem` has the
+/// same sign convention as the rest of the crate, with a spin being +1 when it is true.
+///
+/// The anneal starts from the uniform superposition, the ground state of the transverse field, and it is integrated
+/// with a second order Trotter splitting. Since the state vector has `2^n` amplitudes, it is limited to 20 spins.
+///
+/// The spectral gap is the difference between the two lowest eigenvalues of `H(s)`. When the spin glass has
+/// degenerate ground states, it closes at the end of the anneal, so the minimum gap is only meaningful for spin glasses
+/// with a unique ground state. The eigenvalues are found with the Lanczos method without reorthogonalization, which
+/// can make spurious copies of the lowest eigenvalue, known as ghost eigenvalues, show up among the higher ones. The
+/// second eigenvalue is found in a second run, with the eigenvector of the lowest one shifted out of the way, but that
+/// eigenvector is only as accurate as `lanczos_iterations` allow, and what is left of it can make the gap come out too
+/// small.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::exact_quantum_annealing::{exact_quantum_annealing, ExactQuantumAnnealingConfiguration};
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// // A copy gate whose input is biased towards +1
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![1.0, 0.0];
+/// // Slow enough to be adiabatic
+/// let exact_quantum_annealing_configuration = ExactQuantumAnnealingConfiguration {
+///     annealing_time: 30.0,
+///     ..Default::default()
+/// };
+///
+/// let quantum_evolution = exact_quantum_annealing(
+///     &copy_gate_interactions,
+///     &copy_gate_external_magnetic_field,
+///     Some(&exact_quantum_annealing_configuration),
+/// );
+///
+/// assert!(quantum_evolution.ground_state_probability > 0.99);
+/// assert!(quantum_evolution.probabilities[0b11] > 0.99);
+/// assert!(quantum_evolution.minimum_gap.1 > 0.5);
+/// ```
+pub fn exact_quantum_annealing(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    configuration_override: Option<&ExactQuantumAnnealingConfiguration>,
+) -> QuantumEvolution {
+    let config = configuration_override.cloned().unwrap_or_default();
+    let n = external_magnetic_field.len();
+    assert!(n > 0, "There are no spins to anneal");
+    assert!(n <= 20, "Exact quantum annealing is limited to 20 spins");
+
+    let problem_energies = problem_energies(interactions, external_magnetic_field);
+    let ground_state_energy = problem_energies
+        .iter()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let is_ground_state: Vec<bool> = problem_energies
+        .iter()
+        .map(|energy| is_ground_state_energy(*energy as f32, ground_state_energy as f32))
+        .collect();
+
+    let amplitudes = evolve(&problem_energies, n, &config);
+    let probabilities: Vec<f32> = amplitudes
+        .iter()
+        .map(|amplitude| amplitude.norm_sqr() as f32)
+        .collect();
+    let ground_state_probability = probabilities
+        .iter()
+        .zip(&is_ground_state)
+        .filter(|(_, ground)| **ground)
+        .map(|(probability, _)| probability)
+        .sum();
+
+    let spectral_gaps: Vec<(f32, f32)> = (0..config.gap_samples)
+        .map(|sample| {
+            let s = if config.gap_samples > 1 {
+                sample as f32 / (config.gap_samples - 1) as f32
+            } else {
+                1.0
+            };
+            let hamiltonian = TransverseFieldIsingHamiltonian {
+                problem_energies: &problem_energies,
+                n,
+                transverse_field: config.transverse_field.value(s) as f64,
+                problem_scale: config.problem_scale.value(s) as f64,
+            };
+            let eigenvalues = hamiltonian.lowest_eigenvalues(2, config.lanczos_iterations);
+            let gap = eigenvalues[1] - eigenvalues[0];
+
+            (s, gap.max(0.0) as f32)
+        })
+        .collect();
+    let minimum_gap = spectral_gaps
+        .iter()
+        .copied()
+        .min_by(|left, right| left.1.total_cmp(&right.1))
+        .unwrap_or((1.0, f32::INFINITY));
+
+    QuantumEvolution {
+        ground_state_probability,
+        probabilities,
+        spectral_gaps,
+        minimum_gap,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+    /// Returns `e^(i phase)`.
+    fn from_phase(phase: f64) -> Self {
+        Complex::new(phase.cos(), phase.sin())
+    }
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+/// Returns the energy of every one of the `2^n` states of the spin glass, where bit `i` of the index of a state is the
+/// value of its `i`-th spin. The states are visited in Gray code order, so that each one is a single flip away from
+/// the previous one.
+fn problem_energies(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+) -> Vec<f64> {
+    let n = external_magnetic_field.len();
+    let couplings = SparseCouplings::new(interactions, external_magnetic_field);
+    let mut spin_chain = SpinChain::new(&couplings, vec![false; n]);
+    let mut energies = vec![0.0; 1 << n];
+    energies[0] = spin_chain.current_energy() as f64;
+    for i in 1_usize..(1 << n) {
+        spin_chain.flip_spin(i.trailing_zeros() as usize);
+        energies[i ^ (i >> 1)] = spin_chain.current_energy() as f64;
+    }
+
+    energies
+}
+
+/// Evolves the uniform superposition under `H(s)` for `annealing_time`, alternating half steps of the diagonal
+/// problem term with full steps of the transverse field, which is a product of single spin rotations.
+fn evolve(
+    problem_energies: &[f64],
+    n: usize,
+    config: &ExactQuantumAnnealingConfiguration,
+) -> Vec<Complex> {
+    let dimension = problem_energies.len();
+    let mut amplitudes = vec![Complex::new((1.0 / dimension as f64).sqrt(), 0.0); dimension];
+    let time_step = config.annealing_time as f64 / config.time_steps.max(1) as f64;
+    for step in 0..config.time_steps {
+        let s = (step as f32 + 0.5) / config.time_steps as f32;
+        let problem_scale = config.problem_scale.value(s) as f64;
+        let transverse_field = config.transverse_field.value(s) as f64;
+
+        let apply_problem_half_step = |amplitudes: &mut Vec<Complex>| {
+            for (amplitude, energy) in amplitudes.iter_mut().zip(problem_energies) {
+                *amplitude = amplitude.mul(Complex::from_phase(
+                    -problem_scale * energy * time_step / 2.0,
+                ));
+            }
+        };
+        apply_problem_half_step(&mut amplitudes);
+        // e^(i A dt σˣ) = cos(A dt) + i sin(A dt) σˣ
+        let diagonal = Complex::new((transverse_field * time_step).cos(), 0.0);
+        let off_diagonal = Complex::new(0.0, (transverse_field * time_step).sin());
+        for spin in 0..n {
+            let bit = 1 << spin;
+            for state in 0..dimension {
+                if state & bit == 0 {
+                    let down = amplitudes[state];
+                    let up = amplitudes[state | bit];
+                    amplitudes[state] = diagonal.mul(down).add(off_diagonal.mul(up));
+                    amplitudes[state | bit] = off_diagonal.mul(down).add(diagonal.mul(up));
+                }
+            }
+        }
+        apply_problem_half_step(&mut amplitudes);
+    }
+
+    amplitudes
+}
+
+/// `H(s) = -A(s) Σ σˣ_i + B(s) H_problem` at a given `s`, which is real and symmetric in the computational basis.
+struct TransverseFieldIsingHamiltonian<'a> {
+    problem_energies: &'a [f64],
+    n: usize,
+    transverse_field: f64,
+    problem_scale: f64,
+}
+
+impl TransverseFieldIsingHamiltonian<'_> {
+    /// Computes `H v` into `result`, plus `shift |d⟩⟨d|v⟩` for every already found eigenvector `d` in `deflated`,
+    /// which moves them away from the bottom of the spectrum.
+    fn apply(&self, vector: &[f64], deflated: &[Vec<f64>], shift: f64, result: &mut [f64]) {
+        for (state, result) in result.iter_mut().enumerate() {
+            let flips: f64 = (0..self.n).map(|spin| vector[state ^ (1 << spin)]).sum();
+            *result = self.problem_scale * self.problem_energies[state] * vector[state]
+                - self.transverse_field * flips;
+        }
+        for eigenvector in deflated {
+            let overlap = shift * dot(eigenvector, vector);
+            for (result, component) in result.iter_mut().zip(eigenvector) {
+                *result += overlap * component;
+            }
+        }
+    }
+
+    /// Returns the `count` lowest eigenvalues, in increasing order. They are found one at a time with the Lanczos
+    /// method, shifting the eigenvectors found so far out of the way.
+    fn lowest_eigenvalues(&self, count: usize, iterations: usize) -> Vec<f64> {
+        let dimension = self.problem_energies.len();
+        let spectral_radius = self.problem_scale.abs()
+            * self
+                .problem_energies
+                .iter()
+                .fold(0.0, |radius: f64, energy| radius.max(energy.abs()))
+            + self.transverse_field.abs() * self.n as f64;
+        let shift = 4.0 * spectral_radius + 1.0;
+        let iterations = iterations.clamp(1, dimension);
+        let mut eigenvalues = vec![];
+        let mut eigenvectors: Vec<Vec<f64>> = vec![];
+        let count = count.min(dimension);
+        for index in 0..count {
+            // A deterministic start vector that is not orthogonal to any eigenvector in practice
+            let start: Vec<f64> = (0..dimension)
+                .map(|state| 1.0 + ((state as f64 + 1.0) * 0.618_033_988_75).fract())
+                .collect();
+            let is_last = index + 1 == count;
+            let (eigenvalue, eigenvector) =
+                self.lanczos(&start, iterations, &eigenvectors, shift, !is_last);
+            eigenvalues.push(eigenvalue);
+            eigenvectors.extend(eigenvector);
+        }
+
+        eigenvalues
+    }
+
+    /// Runs `iterations` steps of the Lanczos method from `start`, and returns the lowest eigenvalue of the resulting
+    /// tridiagonal matrix and, if `with_eigenvector`, its eigenvector. Lanczos vectors are not stored, so the
+    /// eigenvector is assembled in a second pass that repeats the same recurrence. Only three vectors are kept, and
+    /// reused across iterations.
+    fn lanczos(
+        &self,
+        start: &[f64],
+        iterations: usize,
+        deflated: &[Vec<f64>],
+        shift: f64,
+        with_eigenvector: bool,
+    ) -> (f64, Option<Vec<f64>>) {
+        let run = |coefficients: Option<&[f64]>| {
+            let mut alphas = vec![];
+            let mut betas: Vec<f64> = vec![];
+            let mut eigenvector = vec![0.0; start.len()];
+            let mut previous = vec![0.0; start.len()];
+            let mut current = normalized(start);
+            let mut next = vec![0.0; start.len()];
+            for iteration in 0..iterations {
+                if let Some(coefficients) = coefficients {
+                    for (component, value) in eigenvector.iter_mut().zip(&current) {
+                        *component += coefficients[iteration] * value;
+                    }
+                }
+                self.apply(&current, deflated, shift, &mut next);
+                let alpha = dot(&next, &current);
+                let previous_beta = betas.last().copied().unwrap_or(0.0);
+                for ((next, current), previous) in next.iter_mut().zip(&current).zip(&previous) {
+                    *next -= alpha * current + previous_beta * previous;
+                }
+                alphas.push(alpha);
+                let beta = dot(&next, &next).sqrt();
+                if iteration + 1 == iterations || beta < 1e-10 {
+                    break;
+                }
+                betas.push(beta);
+                std::mem::swap(&mut previous, &mut current);
+                std::mem::swap(&mut current, &mut next);
+                for value in &mut current {
+                    *value /= beta;
+                }
+            }
+
+            (alphas, betas, eigenvector)
+        };
+
+        let (alphas, betas, _) = run(None);
+        let eigenvalue = lowest_tridiagonal_eigenvalue(&alphas, &betas);
+        if !with_eigenvector {
+            return (eigenvalue, None);
+        }
+        let coefficients = tridiagonal_eigenvector(&alphas, &betas, eigenvalue);
+        let (_, _, eigenvector) = run(Some(&coefficients));
+
+        (eigenvalue, Some(normalized(&eigenvector)))
+    }
+}
+
+fn dot(left: &[f64], right: &[f64]) -> f64 {
+    left.iter()
+        .zip(right)
+        .map(|(left, right)| left * right)
+        .sum()
+}
+
+fn normalized(vector: &[f64]) -> Vec<f64> {
+    let norm = dot(vector, vector).sqrt();
+    vector.iter().map(|value| value / norm).collect()
+}
+
+/// Finds the lowest eigenvalue of the symmetric tridiagonal matrix with diagonal `alphas` and off-diagonal `betas` by
+/// bisection, counting the eigenvalues below a given value with the signs of its Sturm sequence.
+fn lowest_tridiagonal_eigenvalue(alphas: &[f64], betas: &[f64]) -> f64 {
+    let radius = |i: usize| {
+        let left = if i > 0 { betas[i - 1].abs() } else { 0.0 };
+        let right = if i < betas.len() { betas[i].abs() } else { 0.0 };
+        left + right
+    };
+    let mut low = (0..alphas.len())
+        .map(|i| alphas[i] - radius(i))
+        .fold(f64::INFINITY, f64::min);
+    let mut high = (0..alphas.len())
+        .map(|i| alphas[i] + radius(i))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let eigenvalues_below = |value: f64| {
+        let mut count = 0;
+        let mut pivot = 1.0;
+        for i in 0..alphas.len() {
+            let coupling = if i > 0 {
+                betas[i - 1] * betas[i - 1]
+            } else {
+                0.0
+            };
+            pivot = alphas[i] - value - coupling / pivot;
+            if pivot == 0.0 {
+                pivot = -f64::EPSILON;
+            }
+            if pivot < 0.0 {
+                count += 1;
+            }
+        }
+        count
+    };
+    for _ in 0..200 {
+        let middle = (low + high) / 2.0;
+        if middle <= low || middle >= high {
+            break;
+        }
+        if eigenvalues_below(middle) >= 1 {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+/// Finds the eigenvector of the lowest eigenvalue of the symmetric tridiagonal matrix with diagonal `alphas` and
+/// off-diagonal `betas` by inverse iteration. The shift is slightly below `eigenvalue`, so the shifted matrix is
+/// positive definite and can be solved without pivoting.
+fn tridiagonal_eigenvector(alphas: &[f64], betas: &[f64], eigenvalue: f64) -> Vec<f64> {
+    let size = alphas.len();
+    let scale = alphas
+        .iter()
+        .chain(betas)
+        .fold(1.0, |scale: f64, value| scale.max(value.abs()));
+    let shift = eigenvalue - 1e-10 * scale;
+    let mut vector = vec![1.0; size];
+    for _ in 0..3 {
+        // Thomas algorithm
+        let mut diagonal: Vec<f64> = alphas.iter().map(|alpha| alpha - shift).collect();
+        let mut right_hand_side = vector.clone();
+        for i in 1..size {
+            let factor = betas[i - 1] / diagonal[i - 1];
+            diagonal[i] -= factor * betas[i - 1];
+            right_hand_side[i] -= factor * right_hand_side[i - 1];
+        }
+        vector[size - 1] = right_hand_side[size - 1] / diagonal[size - 1];
+        for i in (0..size - 1).rev() {
+            vector[i] = (right_hand_side[i] - betas[i] * vector[i + 1]) / diagonal[i];
+        }
+        vector = normalized(&vector);
+    }
+
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::exact_quantum_annealing::{
+        exact_quantum_annealing, ExactQuantumAnnealingConfiguration,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_single_spin_gap() {
+        // H(s) = -3(1 - s) σˣ - s σᶻ, whose gap 2 sqrt(9 (1 - s)² + s²) is smallest at s = 0.9
+        let exact_quantum_annealing_configuration = ExactQuantumAnnealingConfiguration {
+            gap_samples: 11,
+            ..Default::default()
+        };
+
+        let quantum_evolution = exact_quantum_annealing(
+            &vec![],
+            &vec![1.0],
+            Some(&exact_quantum_annealing_configuration),
+        );
+
+        let (s, gap) = quantum_evolution.minimum_gap;
+        assert!((s - 0.9).abs() < 1e-6);
+        assert!((gap - 2.0 * 0.9_f32.sqrt()).abs() < 1e-4);
+        assert!((quantum_evolution.spectral_gaps[0].1 - 6.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_independent_spins_gap() {
+        // Independent spins have the gap of the one with the weakest field
+        let external_magnetic_field: Vec<f32> = (0..8).map(|i| 0.5 + 0.1 * i as f32).collect();
+        let exact_quantum_annealing_configuration = ExactQuantumAnnealingConfiguration {
+            time_steps: 10,
+            gap_samples: 5,
+            ..Default::default()
+        };
+
+        let quantum_evolution = exact_quantum_annealing(
+            &vec![],
+            &external_magnetic_field,
+            Some(&exact_quantum_annealing_configuration),
+        );
+
+        for (s, gap) in quantum_evolution.spectral_gaps {
+            let expected_gap = 2.0 * (9.0 * (1.0 - s).powi(2) + (0.5 * s).powi(2)).sqrt();
+            assert!((gap - expected_gap).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_sudden_quench_keeps_the_uniform_superposition() {
+        let exact_quantum_annealing_configuration = ExactQuantumAnnealingConfiguration {
+            annealing_time: 1e-6,
+            gap_samples: 0,
+            ..Default::default()
+        };
+
+        let quantum_evolution = exact_quantum_annealing(
+            &vec![(0, 1, 1.0), (1, 2, -1.0)],
+            &vec![0.0, 0.5, 0.0],
+            Some(&exact_quantum_annealing_configuration),
+        );
+
+        assert!(quantum_evolution
+            .probabilities
+            .iter()
+            .all(|probability| (probability - 0.125).abs() < 1e-4));
+        assert!((quantum_evolution.ground_state_probability - 0.125).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_slow_anneal_is_adiabatic() {
+        let exact_quantum_annealing_configuration = ExactQuantumAnnealingConfiguration {
+            transverse_field: Arc::new(|s: f32| 1.0 - s),
+            problem_scale: Arc::new(|s: f32| s),
+            annealing_time: 50.0,
+            gap_samples: 21,
+            ..Default::default()
+        };
+
+        let quantum_evolution = exact_quantum_annealing(
+            &vec![(0, 1, 1.0), (1, 2, 1.0), (2, 0, 1.0)],
+            &vec![0.5, 0.0, 0.0],
+            Some(&exact_quantum_annealing_configuration),
+        );
+
+        assert!(quantum_evolution.ground_state_probability > 0.99);
+        assert!(quantum_evolution.minimum_gap.1 > 0.1);
+        // At the end of the anneal, the gap is the one between all spins up and all spins down
+        assert!((quantum_evolution.spectral_gaps[20].1 - 1.0).abs() < 1e-4);
+    }
+}
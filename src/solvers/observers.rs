@@ -0,0 +1,184 @@
+use crate::types::Energy;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// What a read of [crate::solvers::simulated_annealing] reports to its [Observer] at the end of every sweep.
+/// - `read`: index of the read, since reads run concurrently
+/// - `sweep`: index of the sweep that just ended
+/// - `temperature`: temperature of the sweep
+/// - `current_energy`: energy of the state at the end of the sweep
+/// - `acceptance_rate`: fraction of the spin flips attempted during the sweep that were accepted
+/// - `best_energy`: lowest energy the read has reached so far
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepReport {
+    pub read: usize,
+    pub sweep: usize,
+    pub temperature: f32,
+    pub current_energy: Energy,
+    pub acceptance_rate: f32,
+    pub best_energy: Energy,
+}
+
+/// An Observer follows the progress of [crate::solvers::simulated_annealing] while it runs, one [SweepReport] at a
+/// time. Returning `ControlFlow::Break(())` stops the anneal at the end of the sweep of the report: every read returns
+/// the state it is in at the end of that same sweep, and if several reads ask to stop, the earliest sweep wins.
+///
+/// Reads run on several threads, so an Observer must be shareable between them. Reads that are already past the sweep
+/// the anneal stops at are rerun up to it, without being observed again, so that the results are the same regardless
+/// of the number of threads, as long as the Observer doesn't depend on how fast the reads run, like [TimeLimit] does. Any
+/// `Fn(&SweepReport) -> ControlFlow<()>` closure is an Observer as well, and so is a `Vec` of them, which stops as
+/// soon as any of them does.
+///
+/// ### Example
+///
+/// ```
+/// use std::ops::ControlFlow;
+/// use std::sync::Arc;
+/// use ernst::solvers::observers::SweepReport;
+/// use ernst::solvers::{simulated_annealing, SimulatedAnnealingConfiguration};
+///
+/// let stop_after_ten_sweeps = |sweep_report: &SweepReport| {
+///     if sweep_report.sweep == 9 {
+///         ControlFlow::Break(())
+///     } else {
+///         ControlFlow::Continue(())
+///     }
+/// };
+/// let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+///     observer: Some(Arc::new(stop_after_ten_sweeps)),
+///     ..Default::default()
+/// };
+///
+/// let sample_set = simulated_annealing(&vec![(0, 1, 1.0)], &vec![0.0, 0.0], Some(&simulated_annealing_configuration));
+///
+/// assert!(sample_set.iter().all(|sample| sample.epoch.unwrap() < 10));
+/// ```
+pub trait Observer: Send + Sync {
+    fn observe(&self, sweep_report: &SweepReport) -> ControlFlow<()>;
+}
+
+impl<F> Observer for F
+where
+    F: Fn(&SweepReport) -> ControlFlow<()> + Send + Sync,
+{
+    fn observe(&self, sweep_report: &SweepReport) -> ControlFlow<()> {
+        self(sweep_report)
+    }
+}
+
+impl Observer for Vec<Arc<dyn Observer>> {
+    fn observe(&self, sweep_report: &SweepReport) -> ControlFlow<()> {
+        let mut control_flow = ControlFlow::Continue(());
+        for observer in self {
+            if observer.observe(sweep_report).is_break() {
+                control_flow = ControlFlow::Break(());
+            }
+        }
+
+        control_flow
+    }
+}
+
+/// Stops the anneal as soon as any read reaches `energy`, or lower.
+pub struct TargetEnergy {
+    pub energy: Energy,
+}
+impl Observer for TargetEnergy {
+    fn observe(&self, sweep_report: &SweepReport) -> ControlFlow<()> {
+        if sweep_report.best_energy <= self.energy {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// Stops the anneal once `limit` has elapsed since the TimeLimit was created.
+pub struct TimeLimit {
+    deadline: Instant,
+}
+impl TimeLimit {
+    pub fn new(limit: Duration) -> Self {
+        TimeLimit {
+            deadline: Instant::now() + limit,
+        }
+    }
+}
+impl Observer for TimeLimit {
+    fn observe(&self, _sweep_report: &SweepReport) -> ControlFlow<()> {
+        if Instant::now() >= self.deadline {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// Writes every [SweepReport] as a line of CSV to `writer`, after a header with the names of the columns. It never
+/// stops the anneal. Since reads run concurrently, lines of different reads are interleaved.
+///
+/// ### Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use ernst::solvers::observers::CsvLogger;
+/// use ernst::solvers::{simulated_annealing, SimulatedAnnealingConfiguration};
+///
+/// let csv_logger = Arc::new(CsvLogger::new(vec![]));
+/// let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+///     sweeps: 100,
+///     num_reads: 2,
+///     observer: Some(csv_logger.clone()),
+///     ..Default::default()
+/// };
+///
+/// simulated_annealing(&vec![(0, 1, 1.0)], &vec![0.0, 0.0], Some(&simulated_annealing_configuration));
+/// drop(simulated_annealing_configuration);
+///
+/// let csv = String::from_utf8(Arc::into_inner(csv_logger).unwrap().into_inner()).unwrap();
+/// assert_eq!(1 + 2 * 100, csv.lines().count());
+/// assert_eq!(
+///     "read,sweep,temperature,current_energy,acceptance_rate,best_energy",
+///     csv.lines().next().unwrap()
+/// );
+/// ```
+pub struct CsvLogger<W: Write + Send> {
+    writer: Mutex<W>,
+}
+impl<W: Write + Send> CsvLogger<W> {
+    pub fn new(mut writer: W) -> Self {
+        writeln!(
+            writer,
+            "read,sweep,temperature,current_energy,acceptance_rate,best_energy"
+        )
+        .expect("Failed to write the CSV header");
+
+        CsvLogger {
+            writer: Mutex::new(writer),
+        }
+    }
+    /// Returns the writer, with everything that was logged so far.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap()
+    }
+}
+impl<W: Write + Send> Observer for CsvLogger<W> {
+    fn observe(&self, sweep_report: &SweepReport) -> ControlFlow<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            sweep_report.read,
+            sweep_report.sweep,
+            sweep_report.temperature,
+            sweep_report.current_energy,
+            sweep_report.acceptance_rate,
+            sweep_report.best_energy
+        )
+        .expect("Failed to write to the CSV log");
+
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,346 @@
+use crate::hamiltonian::{SparseCouplings, SpinChain};
+use crate::sample_set::{Sample, SampleSet};
+use crate::solvers::cluster_moves::{houdayer_move, swendsen_wang_move, wolff_move, ClusterMove};
+use crate::solvers::schedules::{AnnealingSchedule, LinearInBeta};
+use crate::solvers::{
+    annealing_temperatures, read_seed, single_flip_sweep, thread_count, AcceptanceRule, SweepOrder,
+};
+use crate::types::{Energy, ExternalMagneticField, Interactions, State};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+/// Parameters for population annealing.
+/// - `initial_temperature`: temperature of the first step; if None, it's picked with
+///   [crate::solvers::schedules::automatic_temperatures]
+/// - `final_temperature`: temperature of the last step; if None, it's picked with
+///   [crate::solvers::schedules::automatic_temperatures]
+/// - `schedule`: how the temperature goes from `initial_temperature` to `final_temperature`
+/// - `temperature_steps`: number of temperatures the population is resampled at
+/// - `population_size`: number of replicas
+/// - `sweeps_per_step`: number of sweeps every replica goes through after each resampling
+/// - `sweep_order`: the order in which spins are visited during a sweep
+/// - `num_threads`: number of threads the replicas are spread across; if None, as many as the available parallelism
+/// - `seed`: rng seed that ensures the whole process to be repeatable, regardless of `num_threads`
+/// - `cluster_move`: if given, it is made after every sweep; with [ClusterMove::Houdayer], the population is shuffled
+///   after every resampling and consecutive replicas are paired up
+#[derive(Clone)]
+pub struct PopulationAnnealingConfiguration {
+    pub initial_temperature: Option<f32>,
+    pub final_temperature: Option<f32>,
+    pub schedule: Arc<dyn AnnealingSchedule>,
+    pub temperature_steps: usize,
+    pub population_size: usize,
+    pub sweeps_per_step: usize,
+    pub sweep_order: SweepOrder,
+    pub num_threads: Option<usize>,
+    pub seed: u64,
+    pub cluster_move: Option<ClusterMove>,
+}
+
+impl Default for PopulationAnnealingConfiguration {
+    fn default() -> Self {
+        PopulationAnnealingConfiguration {
+            initial_temperature: None,
+            final_temperature: None,
+            schedule: Arc::new(LinearInBeta),
+            temperature_steps: 100,
+            population_size: 1000,
+            sweeps_per_step: 10,
+            sweep_order: SweepOrder::Sequential,
+            num_threads: None,
+            seed: 42,
+            cluster_move: None,
+        }
+    }
+}
+
+/// The outcome of [population_annealing].
+/// - `sample_set`: the final population, aggregated, so that its lowest samples are the ground states found
+/// - `free_energy`: estimate of the free energy at the final temperature
+/// - `effective_population_size`: effective number of independent replicas at the last resampling, which is close to
+///   the population size when the temperature steps are small enough
+pub struct PopulationAnnealingResult {
+    pub sample_set: SampleSet,
+    pub free_energy: Energy,
+    pub effective_population_size: f32,
+}
+
+/// Explores the energy landscape of the spin glass whose interaction terms and external magnetic field
+/// are given as the `interactions` and `external_magnetic_field` arguments with population annealing.
+///
+/// A population of replicas starts from random states, i.e. in equilibrium at infinite temperature. At every
+/// temperature step, the population is resampled according to the Boltzmann weight of each replica at the new
+/// temperature, and then every replica goes through Metropolis sweeps at it. The normalisation of the weights gives
+/// the ratio of the partition functions of consecutive temperatures, from which the free energy is estimated. Every
+/// replica has its own seed at every step, derived from the one of the configuration.
+///
+/// ### Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use ernst::solvers::population_annealing::{population_annealing, PopulationAnnealingConfiguration};
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+/// let population_annealing_configuration = PopulationAnnealingConfiguration {
+///     final_temperature: Some(0.1),
+///     ..Default::default()
+/// };
+///
+/// let result = population_annealing(
+///     &copy_gate_interactions,
+///     &copy_gate_external_magnetic_field,
+///     Some(&population_annealing_configuration),
+/// );
+///
+/// // At low temperature, the free energy approaches the ground state energy minus the entropy of its two states
+/// let expected_free_energy = -1.0 - 0.1 * 2.0_f32.ln();
+/// assert!((result.free_energy - expected_free_energy).abs() < 1e-2);
+/// let ground_states: HashSet<_> = result.sample_set.lowest().into_iter().map(|sample| sample.state).collect();
+/// assert_eq!(HashSet::from([vec![false, false], vec![true, true]]), ground_states);
+/// ```
+pub fn population_annealing(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    configuration_override: Option<&PopulationAnnealingConfiguration>,
+) -> PopulationAnnealingResult {
+    let config = configuration_override.cloned().unwrap_or_default();
+    assert!(
+        config.population_size > 0,
+        "The population needs at least one replica"
+    );
+    assert!(
+        config.temperature_steps > 0,
+        "Population annealing needs at least one temperature step"
+    );
+    let temperatures = annealing_temperatures(
+        interactions,
+        external_magnetic_field,
+        config.initial_temperature,
+        config.final_temperature,
+        config.schedule.as_ref(),
+        config.temperature_steps,
+    );
+
+    let couplings = SparseCouplings::new(interactions, external_magnetic_field);
+    let n = couplings.len();
+    let mut population: Vec<SpinChain> = (0..config.population_size)
+        .map(|replica| {
+            let mut rng = StdRng::seed_from_u64(read_seed(config.seed, replica));
+            let initial_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+            SpinChain::new(&couplings, initial_state)
+        })
+        .collect();
+    let num_threads = thread_count(config.num_threads, config.population_size);
+    let mut resampling_rng = StdRng::seed_from_u64(config.seed);
+    let mut log_partition_function = n as f64 * std::f64::consts::LN_2;
+    let mut beta = 0.0;
+    let mut effective_population_size = config.population_size as f32;
+
+    for (step, temperature) in temperatures.iter().enumerate() {
+        let next_beta = 1.0 / temperature.into_inner() as f64;
+        let energies: Vec<f64> = population
+            .iter()
+            .map(|replica| replica.current_energy() as f64)
+            .collect();
+        let lowest_energy = energies.iter().copied().fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = energies
+            .iter()
+            .map(|energy| (-(next_beta - beta) * (energy - lowest_energy)).exp())
+            .collect();
+        let total_weight: f64 = weights.iter().sum();
+        let squared_total_weight: f64 = weights.iter().map(|weight| weight * weight).sum();
+        log_partition_function += -(next_beta - beta) * lowest_energy
+            + (total_weight / config.population_size as f64).ln();
+        effective_population_size = (total_weight * total_weight / squared_total_weight) as f32;
+        population = resample(&population, &weights, &mut resampling_rng);
+        if config.cluster_move == Some(ClusterMove::Houdayer) {
+            // Copies of the same replica are next to each other after resampling, and would make useless pairs
+            population.shuffle(&mut resampling_rng);
+        }
+        beta = next_beta;
+
+        let step_seed = read_seed(config.seed, config.population_size + step);
+        let mut replicas_per_thread = config.population_size.div_ceil(num_threads);
+        if config.cluster_move == Some(ClusterMove::Houdayer) {
+            // Pairs are never split across threads
+            replicas_per_thread += replicas_per_thread % 2;
+        }
+        std::thread::scope(|scope| {
+            for (chunk_index, chunk) in population.chunks_mut(replicas_per_thread).enumerate() {
+                let config = &config;
+                scope.spawn(move || {
+                    let mut rngs: Vec<StdRng> = (0..chunk.len())
+                        .map(|offset| {
+                            let replica_index = chunk_index * replicas_per_thread + offset;
+                            StdRng::seed_from_u64(read_seed(step_seed, replica_index))
+                        })
+                        .collect();
+                    for _ in 0..config.sweeps_per_step {
+                        for (replica, rng) in chunk.iter_mut().zip(rngs.iter_mut()) {
+                            single_flip_sweep(
+                                replica,
+                                *temperature,
+                                config.sweep_order,
+                                AcceptanceRule::Metropolis,
+                                rng,
+                                |_, _| {},
+                            );
+                            match config.cluster_move {
+                                Some(ClusterMove::Wolff) => {
+                                    wolff_move(replica, *temperature, rng, |_, _| {});
+                                }
+                                Some(ClusterMove::SwendsenWang) => {
+                                    swendsen_wang_move(replica, *temperature, rng, |_, _| {});
+                                }
+                                _ => {}
+                            }
+                        }
+                        if config.cluster_move == Some(ClusterMove::Houdayer) {
+                            for (pair, rng) in
+                                chunk.chunks_exact_mut(2).zip(rngs.iter_mut().step_by(2))
+                            {
+                                let [first, second] = pair else {
+                                    unreachable!()
+                                };
+                                houdayer_move(first, second, rng, |_, _| {});
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    let final_temperature = temperatures.last().unwrap().into_inner() as f64;
+    let sample_set: SampleSet = population
+        .iter()
+        .map(|replica| Sample::new(couplings.energy(&replica.spins), replica.spins.clone()))
+        .collect();
+
+    PopulationAnnealingResult {
+        sample_set: sample_set.aggregate(),
+        free_energy: (-final_temperature * log_partition_function) as Energy,
+        effective_population_size,
+    }
+}
+
+/// Draws a new population of the same size from `population`, where every replica is expected to be copied in
+/// proportion to its weight. Systematic resampling is used, so that the number of copies of every replica is as close
+/// as possible to the expected one.
+fn resample<'a>(
+    population: &[SpinChain<'a>],
+    weights: &[f64],
+    rng: &mut StdRng,
+) -> Vec<SpinChain<'a>> {
+    let population_size = population.len();
+    let total_weight: f64 = weights.iter().sum();
+    let offset = rng.gen::<f64>();
+    let mut resampled_population = Vec::with_capacity(population_size);
+    let mut cumulative_weight = 0.0;
+    let mut replica = 0;
+    for draw in 0..population_size {
+        let position = (draw as f64 + offset) / population_size as f64 * total_weight;
+        while replica < population_size - 1 && cumulative_weight + weights[replica] <= position {
+            cumulative_weight += weights[replica];
+            replica += 1;
+        }
+        resampled_population.push(population[replica].clone());
+    }
+
+    resampled_population
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::cluster_moves::ClusterMove;
+    use crate::solvers::population_annealing::{
+        population_annealing, PopulationAnnealingConfiguration,
+    };
+    use crate::solvers::{energy, find_all_ground_states};
+
+    #[test]
+    fn test_population_annealing_on_a_2d_spin_glass() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(4, 4, CouplingDistribution::PlusMinusJ, 7);
+        let final_temperature = 0.5;
+        let population_annealing_configuration = PopulationAnnealingConfiguration {
+            initial_temperature: Some(5.0),
+            final_temperature: Some(final_temperature),
+            population_size: 500,
+            temperature_steps: 50,
+            ..Default::default()
+        };
+
+        let result = population_annealing(
+            &interactions,
+            &external_magnetic_field,
+            Some(&population_annealing_configuration),
+        );
+
+        let n = external_magnetic_field.len();
+        let partition_function: f64 = (0..1_usize << n)
+            .map(|bits| {
+                let state = (0..n).map(|i| bits >> i & 1 == 1).collect();
+                let energy = energy(&interactions, &external_magnetic_field, &state) as f64;
+                (-energy / final_temperature as f64).exp()
+            })
+            .sum();
+        let exact_free_energy = -final_temperature as f64 * partition_function.ln();
+        let ground_state_energy = find_all_ground_states(&interactions, &external_magnetic_field)
+            .lowest_energy()
+            .unwrap();
+
+        assert!(
+            (result.free_energy as f64 - exact_free_energy).abs() < 0.01 * exact_free_energy.abs()
+        );
+        assert_eq!(Some(ground_state_energy), result.sample_set.lowest_energy());
+        assert_eq!(500, result.sample_set.num_occurrences());
+        assert!(result.effective_population_size > 250.0);
+        assert!(result.effective_population_size <= 500.0);
+    }
+
+    #[test]
+    fn test_population_annealing_with_houdayer_moves_is_independent_of_num_threads() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(4, 4, CouplingDistribution::Gaussian, 7);
+        let ground_state_energy = find_all_ground_states(&interactions, &external_magnetic_field)
+            .lowest_energy()
+            .unwrap();
+        let result_with = |num_threads: usize| {
+            let population_annealing_configuration = PopulationAnnealingConfiguration {
+                final_temperature: Some(0.2),
+                population_size: 101,
+                temperature_steps: 20,
+                num_threads: Some(num_threads),
+                cluster_move: Some(ClusterMove::Houdayer),
+                ..Default::default()
+            };
+
+            population_annealing(
+                &interactions,
+                &external_magnetic_field,
+                Some(&population_annealing_configuration),
+            )
+        };
+
+        let single_threaded_result = result_with(1);
+        assert!(
+            (single_threaded_result.sample_set.lowest_energy().unwrap() - ground_state_energy)
+                .abs()
+                < 1e-3
+        );
+        for num_threads in [2, 3, 8] {
+            let result = result_with(num_threads);
+            assert_eq!(single_threaded_result.sample_set, result.sample_set);
+            assert_eq!(single_threaded_result.free_energy, result.free_energy);
+        }
+    }
+}
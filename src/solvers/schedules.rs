@@ -0,0 +1,271 @@
+use crate::types::{ExternalMagneticField, Interactions};
+
+/// An AnnealingSchedule decides the temperature of every sweep of [crate::solvers::simulated_annealing].
+/// `progress` goes from 0.0 at the first sweep to 1.0 at the last one, and `initial_temperature` and
+/// `final_temperature` are the ones of the [crate::solvers::SimulatedAnnealingConfiguration].
+///
+/// Any `Fn(f32, f32, f32) -> f32` closure that takes `progress`, `initial_temperature` and `final_temperature`
+/// is an AnnealingSchedule as well.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::schedules::{AnnealingSchedule, Geometric};
+///
+/// let square_root = |progress: f32, initial_temperature: f32, final_temperature: f32| {
+///     initial_temperature + (final_temperature - initial_temperature) * progress.sqrt()
+/// };
+///
+/// assert_eq!(5.5, square_root.temperature(0.25, 10.0, 1.0));
+/// assert_eq!(1.0, Geometric.temperature(1.0, 10.0, 1.0));
+/// ```
+pub trait AnnealingSchedule: Send + Sync {
+    fn temperature(&self, progress: f32, initial_temperature: f32, final_temperature: f32) -> f32;
+}
+
+impl<F> AnnealingSchedule for F
+where
+    F: Fn(f32, f32, f32) -> f32 + Send + Sync,
+{
+    fn temperature(&self, progress: f32, initial_temperature: f32, final_temperature: f32) -> f32 {
+        self(progress, initial_temperature, final_temperature)
+    }
+}
+
+/// The temperature decreases by the same amount at every sweep.
+pub struct Linear;
+impl AnnealingSchedule for Linear {
+    fn temperature(&self, progress: f32, initial_temperature: f32, final_temperature: f32) -> f32 {
+        initial_temperature + (final_temperature - initial_temperature) * progress
+    }
+}
+
+/// The temperature decreases by the same factor at every sweep.
+pub struct Geometric;
+impl AnnealingSchedule for Geometric {
+    fn temperature(&self, progress: f32, initial_temperature: f32, final_temperature: f32) -> f32 {
+        initial_temperature * (final_temperature / initial_temperature).powf(progress)
+    }
+}
+
+/// The inverse temperature increases by the same amount at every sweep.
+pub struct LinearInBeta;
+impl AnnealingSchedule for LinearInBeta {
+    fn temperature(&self, progress: f32, initial_temperature: f32, final_temperature: f32) -> f32 {
+        let initial_beta = 1.0 / initial_temperature;
+        let final_beta = 1.0 / final_temperature;
+
+        1.0 / (initial_beta + (final_beta - initial_beta) * progress)
+    }
+}
+
+/// The temperature decays exponentially towards `final_temperature`, with `decay` being the rate of the decay over
+/// the whole schedule. It is rescaled so that `final_temperature` is reached at the last sweep. As `decay` goes to 0.0
+/// the schedule becomes [Linear].
+pub struct Exponential {
+    decay: f32,
+}
+impl Exponential {
+    /// Creates a new Exponential schedule. `decay` must not be negative.
+    pub fn new(decay: f32) -> Self {
+        assert!(decay >= 0.0, "The decay must not be negative");
+
+        Exponential { decay }
+    }
+    /// Returns the rate of the decay over the whole schedule.
+    pub fn decay(&self) -> f32 {
+        self.decay
+    }
+}
+impl Default for Exponential {
+    fn default() -> Self {
+        Exponential::new(5.0)
+    }
+}
+impl AnnealingSchedule for Exponential {
+    fn temperature(&self, progress: f32, initial_temperature: f32, final_temperature: f32) -> f32 {
+        if self.decay < f32::EPSILON {
+            return Linear.temperature(progress, initial_temperature, final_temperature);
+        }
+        let remaining =
+            ((-self.decay * progress).exp_m1() - (-self.decay).exp_m1()) / -(-self.decay).exp_m1();
+
+        final_temperature + (initial_temperature - final_temperature) * remaining
+    }
+}
+
+/// The temperature is linearly interpolated between `(progress, temperature)` points. Before the first point and
+/// after the last one it stays constant. The initial and final temperatures of the configuration are ignored.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::schedules::{AnnealingSchedule, Piecewise};
+///
+/// // Quench to 1.0, hold it, and then cool down to 0.1
+/// let schedule = Piecewise::new(vec![(0.0, 10.0), (0.2, 1.0), (0.8, 1.0), (1.0, 0.1)]);
+///
+/// assert_eq!(5.5, schedule.temperature(0.1, 0.0, 0.0));
+/// assert_eq!(1.0, schedule.temperature(0.5, 0.0, 0.0));
+/// ```
+pub struct Piecewise {
+    points: Vec<(f32, f32)>,
+}
+impl Piecewise {
+    /// Creates a new Piecewise schedule. There must be at least one point, and they must be sorted by progress.
+    pub fn new(points: Vec<(f32, f32)>) -> Self {
+        assert!(
+            !points.is_empty(),
+            "A piecewise schedule needs at least one point"
+        );
+        assert!(
+            points.windows(2).all(|pair| pair[0].0 <= pair[1].0),
+            "The points of a piecewise schedule must be sorted by progress"
+        );
+
+        Piecewise { points }
+    }
+    /// Returns the `(progress, temperature)` points of the schedule.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+}
+impl AnnealingSchedule for Piecewise {
+    fn temperature(
+        &self,
+        progress: f32,
+        _initial_temperature: f32,
+        _final_temperature: f32,
+    ) -> f32 {
+        let next = self
+            .points
+            .iter()
+            .position(|(point_progress, _)| *point_progress > progress);
+        match next {
+            None => self.points.last().unwrap().1,
+            Some(0) => self.points[0].1,
+            Some(next) => {
+                let (left_progress, left_temperature) = self.points[next - 1];
+                let (right_progress, right_temperature) = self.points[next];
+                let fraction = (progress - left_progress) / (right_progress - left_progress);
+
+                left_temperature + (right_temperature - left_temperature) * fraction
+            }
+        }
+    }
+}
+
+/// Picks the initial and final temperatures of an anneal from the coupling magnitudes of the spin glass. At the
+/// initial temperature, the largest possible energy increase of a single spin flip is accepted with probability 50%;
+/// at the final temperature, the smallest one is accepted with probability 1%.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::schedules::automatic_temperatures;
+///
+/// let (initial_temperature, final_temperature) = automatic_temperatures(&vec![(0, 1, 1.0)], &vec![0.0, 0.0]);
+///
+/// assert!((initial_temperature - 2.0 / 2.0_f32.ln()).abs() < 1e-5);
+/// assert!((final_temperature - 2.0 / 100.0_f32.ln()).abs() < 1e-5);
+/// ```
+pub fn automatic_temperatures(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+) -> (f32, f32) {
+    let mut local_field_bounds: Vec<f32> =
+        external_magnetic_field.iter().map(|h| h.abs()).collect();
+    let mut smallest_magnitude = external_magnetic_field
+        .iter()
+        .map(|h| h.abs())
+        .filter(|magnitude| *magnitude > 0.0)
+        .fold(f32::INFINITY, f32::min);
+    for (i, j, interaction_strength) in interactions {
+        assert!(
+            *i < local_field_bounds.len() && *j < local_field_bounds.len(),
+            "The interaction vector refers to spins that have no external magnetic field"
+        );
+        local_field_bounds[*i] += interaction_strength.abs();
+        local_field_bounds[*j] += interaction_strength.abs();
+        if *interaction_strength != 0.0 {
+            smallest_magnitude = smallest_magnitude.min(interaction_strength.abs());
+        }
+    }
+    let largest_delta_energy = 2.0 * local_field_bounds.iter().fold(0.0, |a: f32, b| a.max(*b));
+    let smallest_delta_energy = 2.0 * smallest_magnitude;
+    if !smallest_delta_energy.is_finite() {
+        return (1.0, 1.0);
+    }
+
+    (
+        largest_delta_energy / 2.0_f32.ln(),
+        smallest_delta_energy / 100.0_f32.ln(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::schedules::{
+        automatic_temperatures, AnnealingSchedule, Exponential, Geometric, Linear, LinearInBeta,
+        Piecewise,
+    };
+
+    #[test]
+    fn test_schedules_go_from_initial_to_final_temperature() {
+        let schedules: Vec<Box<dyn AnnealingSchedule>> = vec![
+            Box::new(Linear),
+            Box::new(Geometric),
+            Box::new(LinearInBeta),
+            Box::new(Exponential::default()),
+            Box::new(Piecewise::new(vec![(0.0, 10.0), (1.0, 0.1)])),
+        ];
+
+        for schedule in schedules {
+            assert!((schedule.temperature(0.0, 10.0, 0.1) - 10.0).abs() < 1e-4);
+            assert!((schedule.temperature(1.0, 10.0, 0.1) - 0.1).abs() < 1e-4);
+            let temperatures: Vec<f32> = (0..=10)
+                .map(|step| schedule.temperature(step as f32 / 10.0, 10.0, 0.1))
+                .collect();
+            assert!(temperatures.windows(2).all(|pair| pair[0] >= pair[1]));
+        }
+    }
+
+    #[test]
+    fn test_exponential_schedule_becomes_linear_as_decay_goes_to_zero() {
+        for decay in [0.0, 1e-9, 1e-4] {
+            let schedule = Exponential::new(decay);
+            for step in 0..=10 {
+                let progress = step as f32 / 10.0;
+                let temperature = schedule.temperature(progress, 10.0, 0.1);
+                let linear_temperature = Linear.temperature(progress, 10.0, 0.1);
+                assert!((temperature - linear_temperature).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The decay must not be negative")]
+    fn test_exponential_schedule_rejects_negative_decay() {
+        Exponential::new(-1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The points of a piecewise schedule must be sorted by progress")]
+    fn test_piecewise_schedule_rejects_unsorted_points() {
+        Piecewise::new(vec![(0.0, 10.0), (1.0, 0.1), (0.5, 1.0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "A piecewise schedule needs at least one point")]
+    fn test_piecewise_schedule_rejects_no_points() {
+        Piecewise::new(vec![]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The interaction vector refers to spins that have no external magnetic field"
+    )]
+    fn test_automatic_temperatures_rejects_spins_without_external_magnetic_field() {
+        automatic_temperatures(&vec![(0, 2, 1.0)], &vec![0.0, 0.0]);
+    }
+}
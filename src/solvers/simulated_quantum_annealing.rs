@@ -0,0 +1,263 @@
+use crate::hamiltonian::{SparseCouplings, SpinChain};
+use crate::sample_set::{Sample, SampleSet};
+use crate::solvers::run_seeded_jobs;
+use crate::solvers::schedules::{AnnealingSchedule, Piecewise};
+use crate::types::{ExternalMagneticField, Interactions, State};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::sync::Arc;
+
+/// An AnnealingFunction gives the magnitude of one of the terms of the quantum annealing Hamiltonian
+/// `H(s) = -A(s) Σ σˣ_i + B(s) H_problem` as a function of the normalised annealing time `s`, which goes from 0.0 at
+/// the start of the anneal to 1.0 at its end.
+///
+/// Any `Fn(f32) -> f32` closure is an AnnealingFunction, and so is a [Piecewise] schedule, which makes it possible to
+/// use tabulated hardware schedules.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::schedules::Piecewise;
+/// use ernst::solvers::simulated_quantum_annealing::AnnealingFunction;
+///
+/// let transverse_field = |s: f32| 2.0 * (1.0 - s);
+/// let problem_scale = Piecewise::new(vec![(0.0, 0.0), (0.5, 0.2), (1.0, 1.0)]);
+///
+/// assert_eq!(1.0, transverse_field.value(0.5));
+/// assert_eq!(0.6, problem_scale.value(0.75));
+/// ```
+pub trait AnnealingFunction: Send + Sync {
+    fn value(&self, s: f32) -> f32;
+}
+
+impl<F> AnnealingFunction for F
+where
+    F: Fn(f32) -> f32 + Send + Sync,
+{
+    fn value(&self, s: f32) -> f32 {
+        self(s)
+    }
+}
+
+impl AnnealingFunction for Piecewise {
+    fn value(&self, s: f32) -> f32 {
+        self.temperature(s, 0.0, 0.0)
+    }
+}
+
+/// Parameters for simulated quantum annealing.
+/// - `transverse_field`: A(s), the strength of the transverse field
+/// - `problem_scale`: B(s), the scale of the spin glass
+/// - `temperature`: the temperature the whole anneal runs at, in the same units as A(s) and B(s)
+/// - `trotter_slices`: number of imaginary time slices the quantum spins are replicated into
+/// - `sweeps`: number of sweeps of every read; a sweep attempts a flip of every spin of every slice, and a flip of
+///   every spin across all slices at once
+/// - `num_reads`: number of independent anneals, each one starting from a random state
+/// - `num_threads`, `seed`: how the reads are spread across threads and seeded, like the ones of
+///   [crate::solvers::SimulatedAnnealingConfiguration]
+#[derive(Clone)]
+pub struct SimulatedQuantumAnnealingConfiguration {
+    pub transverse_field: Arc<dyn AnnealingFunction>,
+    pub problem_scale: Arc<dyn AnnealingFunction>,
+    pub temperature: f32,
+    pub trotter_slices: usize,
+    pub sweeps: usize,
+    pub num_reads: usize,
+    pub num_threads: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for SimulatedQuantumAnnealingConfiguration {
+    fn default() -> Self {
+        SimulatedQuantumAnnealingConfiguration {
+            transverse_field: Arc::new(|s: f32| 3.0 * (1.0 - s)),
+            problem_scale: Arc::new(|s: f32| s),
+            temperature: 0.05,
+            trotter_slices: 32,
+            sweeps: 1000,
+            num_reads: 10,
+            num_threads: None,
+            seed: 42,
+        }
+    }
+}
+
+/// Simulates quantum annealing of the spin glass whose interaction terms and external magnetic field are given as the
+/// `interactions` and `external_magnetic_field` arguments with path-integral Monte Carlo.
+///
+/// The transverse field Ising Hamiltonian `H(s) = -A(s) Σ σˣ_i + B(s) H_problem` at temperature T is mapped, through
+/// the Suzuki-Trotter decomposition, onto `trotter_slices` classical copies of the spin glass, each one scaled by
+/// `B(s) / trotter_slices`, in which every spin is ferromagnetically coupled to itself in the neighbouring slices with
+/// strength `T / 2 * ln(coth(A(s) / (trotter_slices * T)))`. The copies are then sampled with Metropolis sweeps while
+/// `s` goes from 0.0 to 1.0.
+///
+/// It will run `num_reads` independent anneals, spread across threads, and return the slice of lowest energy of each
+/// one of them. Every read has its own seed, derived from the one of the configuration.
+///
+/// ### Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use ernst::solvers::simulated_quantum_annealing::simulated_quantum_annealing;
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+///
+/// let actual_states: HashSet<_> =
+///     simulated_quantum_annealing(&copy_gate_interactions, &copy_gate_external_magnetic_field, None)
+///         .into_iter()
+///         .map(|sample| (sample.energy as i32, sample.state))
+///         .collect();
+/// let expected_states = HashSet::from([(-1, vec![false, false]), (-1, vec![true, true])]);
+///
+/// assert_eq!(expected_states, actual_states)
+/// ```
+pub fn simulated_quantum_annealing(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    configuration_override: Option<&SimulatedQuantumAnnealingConfiguration>,
+) -> SampleSet {
+    let config = configuration_override.cloned().unwrap_or_default();
+    assert!(
+        config.trotter_slices > 0,
+        "Simulated quantum annealing needs at least one Trotter slice"
+    );
+    assert!(
+        config.temperature > 0.0,
+        "Simulated quantum annealing needs a positive temperature"
+    );
+
+    let couplings = SparseCouplings::new(interactions, external_magnetic_field);
+
+    run_seeded_jobs(
+        config.num_reads,
+        config.num_threads,
+        config.seed,
+        |read_index, rng| quantum_anneal(&couplings, &config, read_index, rng),
+    )
+    .into_iter()
+    .collect()
+}
+
+/// Runs the `read_index`-th read of simulated quantum annealing, from a random state of every slice.
+fn quantum_anneal(
+    couplings: &SparseCouplings,
+    config: &SimulatedQuantumAnnealingConfiguration,
+    read_index: usize,
+    mut rng: StdRng,
+) -> Sample {
+    let n = couplings.len();
+    let trotter_slices = config.trotter_slices;
+    let beta = 1.0 / config.temperature as f64;
+    let mut slices: Vec<SpinChain> = (0..trotter_slices)
+        .map(|_| {
+            let initial_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+            SpinChain::new(couplings, initial_state)
+        })
+        .collect();
+    let spin_value = |spin: bool| if spin { 1.0 } else { -1.0 };
+
+    for sweep in 0..config.sweeps {
+        let s = if config.sweeps > 1 {
+            sweep as f32 / (config.sweeps - 1) as f32
+        } else {
+            1.0
+        };
+        let problem_beta = beta * config.problem_scale.value(s) as f64 / trotter_slices as f64;
+        // In units of the inverse temperature; a vanishing transverse field freezes the slices together
+        let transverse_field = (config.transverse_field.value(s) as f64).max(1e-12);
+        let slice_coupling = if trotter_slices > 1 {
+            0.5 * (1.0 / (beta * transverse_field / trotter_slices as f64).tanh()).ln()
+        } else {
+            0.0
+        };
+
+        for slice in 0..trotter_slices {
+            let previous_slice = (slice + trotter_slices - 1) % trotter_slices;
+            let next_slice = (slice + 1) % trotter_slices;
+            for spin in 0..n {
+                let spin_value_here = spin_value(slices[slice].spins[spin]);
+                let neighbouring_spins = spin_value(slices[previous_slice].spins[spin])
+                    + spin_value(slices[next_slice].spins[spin]);
+                let delta = problem_beta * slices[slice].delta_energy(spin) as f64
+                    + 2.0 * slice_coupling * spin_value_here * neighbouring_spins;
+                if delta <= 0.0 || (-delta).exp() > rng.gen::<f64>() {
+                    slices[slice].flip_spin(spin);
+                }
+            }
+        }
+
+        // Flipping a spin in every slice at once leaves the couplings between slices untouched
+        for spin in 0..n {
+            let delta = problem_beta
+                * slices
+                    .iter()
+                    .map(|slice| slice.delta_energy(spin) as f64)
+                    .sum::<f64>();
+            if delta <= 0.0 || (-delta).exp() > rng.gen::<f64>() {
+                for slice in &mut slices {
+                    slice.flip_spin(spin);
+                }
+            }
+        }
+    }
+
+    let lowest_slice = slices
+        .into_iter()
+        .min_by(|left, right| left.current_energy().total_cmp(&right.current_energy()))
+        .unwrap();
+
+    Sample {
+        chain_id: Some(read_index),
+        ..Sample::new(couplings.energy(&lowest_slice.spins), lowest_slice.spins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::find_all_ground_states;
+    use crate::solvers::simulated_quantum_annealing::{
+        simulated_quantum_annealing, SimulatedQuantumAnnealingConfiguration,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_simulated_quantum_annealing_finds_ground_state() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(4, 4, CouplingDistribution::PlusMinusJ, 7);
+        let ground_state_energy = find_all_ground_states(&interactions, &external_magnetic_field)
+            .lowest_energy()
+            .unwrap();
+
+        let samples = simulated_quantum_annealing(&interactions, &external_magnetic_field, None);
+
+        assert_eq!(10, samples.len());
+        assert_eq!(Some(ground_state_energy), samples.lowest_energy());
+    }
+
+    #[test]
+    fn test_simulated_quantum_annealing_without_transverse_field() {
+        // A vanishing transverse field makes the coupling between slices diverge, which must not break the anneal
+        let simulated_quantum_annealing_configuration = SimulatedQuantumAnnealingConfiguration {
+            transverse_field: Arc::new(|_s: f32| 0.0),
+            problem_scale: Arc::new(|_s: f32| 1.0),
+            temperature: 0.5,
+            sweeps: 100,
+            ..Default::default()
+        };
+
+        let samples = simulated_quantum_annealing(
+            &vec![(0, 1, 1.0), (1, 2, 1.0)],
+            &vec![1.0, 0.0, 0.0],
+            Some(&simulated_quantum_annealing_configuration),
+        );
+
+        assert!(samples.iter().all(|sample| sample.energy.is_finite()));
+        assert_eq!(Some(-3.0), samples.lowest_energy());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::hamiltonian::TwoLocalHamiltonian;
+use crate::sample_set::{Sample, SampleSet};
+use crate::solvers::{from_compact_state_to_state, run_seeded_jobs};
+use crate::types::{Energy, ExternalMagneticField, Interactions, State};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// Parameters for tabu search.
+/// - `tenure`: number of iterations a flipped spin stays tabu; if None, it's a quarter of the spins, up to 20
+/// - `max_iterations_without_improvement`: a restart ends once this many iterations go by without finding a state
+///   lower than the best one so far
+/// - `num_restarts`: number of independent searches, each one starting from a random state
+/// - `num_threads`, `seed`: how the restarts are spread across threads and seeded, like the reads of
+///   [crate::solvers::SimulatedAnnealingConfiguration]
+#[derive(Clone)]
+pub struct TabuSearchConfiguration {
+    pub tenure: Option<usize>,
+    pub max_iterations_without_improvement: usize,
+    pub num_restarts: usize,
+    pub num_threads: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for TabuSearchConfiguration {
+    fn default() -> Self {
+        TabuSearchConfiguration {
+            tenure: None,
+            max_iterations_without_improvement: 1000,
+            num_restarts: 10,
+            num_threads: None,
+            seed: 42,
+        }
+    }
+}
+
+/// Searches for the ground states of the spin glass whose interaction terms and external magnetic field are given as
+/// the `interactions` and `external_magnetic_field` arguments with tabu search.
+///
+/// At every iteration, the single spin flip that lowers the energy the most, or raises it the least, is made, unless
+/// the spin was flipped less than `tenure` iterations ago. Tabu spins are only flipped if that leads to a state lower
+/// than the best one found so far. Energy changes are known in constant time, since the two-local Hamiltonian of every
+/// restart keeps the energy change of every spin up to date as spins are flipped.
+///
+/// It will run `num_restarts` independent searches, spread across threads, and return the best state of each one of
+/// them, alongside its energy and the iteration in which it was found. Every restart has its own seed, derived from
+/// the one of the configuration.
+///
+/// ### Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use ernst::solvers::tabu_search::tabu_search;
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+///
+/// let actual_states: HashSet<_> = tabu_search(&copy_gate_interactions, &copy_gate_external_magnetic_field, None)
+///   .into_iter()
+///   .map(|sample| (sample.energy as i32, sample.state))
+///   .collect();
+/// let expected_states = HashSet::from([(-1, vec![false, false]), (-1, vec![true, true])]);
+///
+/// assert_eq!(expected_states, actual_states)
+/// ```
+pub fn tabu_search(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    configuration_override: Option<&TabuSearchConfiguration>,
+) -> SampleSet {
+    let config = configuration_override.cloned().unwrap_or_default();
+
+    run_seeded_jobs(
+        config.num_restarts,
+        config.num_threads,
+        config.seed,
+        |restart_index, rng| {
+            search(
+                interactions,
+                external_magnetic_field,
+                &config,
+                restart_index,
+                rng,
+            )
+        },
+    )
+    .into_iter()
+    .collect()
+}
+
+/// Runs the `restart_index`-th restart of tabu search, from a random state.
+fn search(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    config: &TabuSearchConfiguration,
+    restart_index: usize,
+    mut rng: StdRng,
+) -> Sample {
+    let n = external_magnetic_field.len();
+    let tenure = config.tenure.unwrap_or((n / 4).min(20));
+
+    let initial_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let mut two_local_hamiltonian = TwoLocalHamiltonian::new(
+        interactions.clone(),
+        external_magnetic_field.clone(),
+        Some(initial_state),
+    );
+    let mut best_energy = two_local_hamiltonian.current_energy();
+    let mut best_state = two_local_hamiltonian.spins.clone();
+    let mut best_iteration = 0;
+    // The last iteration in which every spin is tabu
+    let mut tabu_until = vec![0; n];
+    let mut iteration = 0;
+
+    while n > 0 && iteration - best_iteration < config.max_iterations_without_improvement {
+        iteration += 1;
+        let current_energy = two_local_hamiltonian.current_energy();
+        let mut best_move: Option<(usize, Energy)> = None;
+        let mut ties = 0;
+        for (spin, spin_tabu_until) in tabu_until.iter().enumerate() {
+            let delta_energy = two_local_hamiltonian.delta_energy(spin);
+            let aspires = current_energy + delta_energy < best_energy;
+            if *spin_tabu_until >= iteration && !aspires {
+                continue;
+            }
+            match best_move {
+                Some((_, best_delta_energy)) if delta_energy > best_delta_energy => {}
+                Some((_, best_delta_energy)) if delta_energy == best_delta_energy => {
+                    // Ties are broken uniformly at random, so that the search doesn't cycle
+                    ties += 1;
+                    if rng.gen_range(0..ties) == 0 {
+                        best_move = Some((spin, delta_energy));
+                    }
+                }
+                _ => {
+                    ties = 1;
+                    best_move = Some((spin, delta_energy));
+                }
+            }
+        }
+        let Some((spin_to_flip, _)) = best_move else {
+            // Every spin is tabu, so the search just waits for the oldest one to be released
+            continue;
+        };
+
+        two_local_hamiltonian.flip_spin(spin_to_flip);
+        tabu_until[spin_to_flip] = iteration + tenure;
+        if two_local_hamiltonian.current_energy() < best_energy {
+            best_energy = two_local_hamiltonian.current_energy();
+            best_state.clone_from(&two_local_hamiltonian.spins);
+            best_iteration = iteration;
+        }
+    }
+
+    Sample {
+        epoch: Some(best_iteration),
+        chain_id: Some(restart_index),
+        ..Sample::new(best_energy, from_compact_state_to_state(best_state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::{edwards_anderson_2d, tile_planting};
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::find_all_ground_states;
+    use crate::solvers::tabu_search::{tabu_search, TabuSearchConfiguration};
+
+    #[test]
+    fn test_tabu_search_finds_ground_state() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(4, 4, CouplingDistribution::Gaussian, 7);
+        let ground_state_energy = find_all_ground_states(&interactions, &external_magnetic_field)
+            .lowest_energy()
+            .unwrap();
+
+        let samples = tabu_search(&interactions, &external_magnetic_field, None);
+
+        assert_eq!(10, samples.len());
+        assert!((samples.lowest_energy().unwrap() - ground_state_energy).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_tabu_search_on_planted_instance() {
+        let planted_instance = tile_planting(12, 12, 0.5, 7);
+        let tabu_search_configuration = TabuSearchConfiguration {
+            max_iterations_without_improvement: 5000,
+            num_restarts: 4,
+            ..Default::default()
+        };
+
+        let samples = tabu_search(
+            &planted_instance.interactions,
+            &planted_instance.external_magnetic_field,
+            Some(&tabu_search_configuration),
+        );
+
+        assert!(
+            (samples.lowest_energy().unwrap() - planted_instance.ground_state_energy).abs() < 1e-4
+        );
+        for sample in &samples {
+            assert!(sample.epoch.unwrap() > 0);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::hamiltonian::TwoLocalHamiltonian;
+use crate::sample_set::ENERGY_RESOLUTION;
+use crate::solvers::density_of_states::DensityOfStates;
+use crate::types::{Energy, ExternalMagneticField, Interactions, State};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Parameters for Wang-Landau sampling.
+/// - `bin_width`: width of the energy bins; if None, every energy level has its own bin, which is only practical when
+///   there are few of them, like for integer couplings
+/// - `flatness`: the histogram is flat once every bin has been visited at least this fraction of the mean number of
+///   visits
+/// - `final_log_modification_factor`: the walk ends once the logarithm of the modification factor drops below this
+/// - `sweeps_per_check`: number of sweeps between flatness checks of the histogram
+/// - `max_sweeps`: the walk ends after this many sweeps, even if it hasn't converged
+/// - `seed`: rng seed that ensures the whole process to be repeatable
+#[derive(Clone)]
+pub struct WangLandauConfiguration {
+    pub bin_width: Option<f32>,
+    pub flatness: f32,
+    pub final_log_modification_factor: f64,
+    pub sweeps_per_check: usize,
+    pub max_sweeps: usize,
+    pub seed: u64,
+}
+
+impl Default for WangLandauConfiguration {
+    fn default() -> Self {
+        WangLandauConfiguration {
+            bin_width: None,
+            flatness: 0.8,
+            final_log_modification_factor: 1e-6,
+            sweeps_per_check: 100,
+            max_sweeps: 1_000_000,
+            seed: 42,
+        }
+    }
+}
+
+/// The outcome of [wang_landau].
+/// - `density_of_states`: the estimated density of states, normalized so that there are `2^n` states in total. When
+///   energies are binned, the energy of every bin is the lowest one that the walk visited in it
+/// - `sweeps`: number of sweeps the walk went through
+/// - `converged`: whether the modification factor reached `final_log_modification_factor` before `max_sweeps`
+pub struct WangLandauResult {
+    pub density_of_states: DensityOfStates,
+    pub sweeps: usize,
+    pub converged: bool,
+}
+
+/// An energy bin of the walk.
+struct Bin {
+    energy: Energy,
+    log_density_of_states: f64,
+    visits: usize,
+}
+
+/// Estimates the density of states of the spin glass whose interaction terms and external magnetic field are given as
+/// the `interactions` and `external_magnetic_field` arguments with Wang-Landau sampling, for spin glasses too large to
+/// walk every state of, like [crate::solvers::partition_function] does.
+///
+/// A random walk over single spin flips accepts every move with probability `min(1, g(E) / g(E'))`, where `g` is the
+/// current estimate of the density of states, which is multiplied by the modification factor `f` at every step in
+/// the bin the walk is in. Once the histogram of visits is flat, `ln f` is halved and the histogram is reset. As soon
+/// as `ln f` drops below `1/t`, where `t` is the number of steps per bin so far, it's set to `1/t` at every step
+/// instead, which avoids the saturation of the error of the original algorithm. Bins are discovered as the walk goes,
+/// starting at the lowest estimate so far. The thermodynamics at any temperature, and the
+/// degeneracy of the ground state, follow from the result, see [DensityOfStates].
+///
+/// The energy of the walk, and the energy change of every move, are kept up to date by a two-local Hamiltonian, like
+/// the one the exact solvers walk every state with.
+///
+/// ### Example
+///
+/// ```
+/// use ernst::solvers::wang_landau::{wang_landau, WangLandauConfiguration};
+///
+/// let s0 = 0;
+/// let z = 1;
+///
+/// let copy_gate_interactions = vec![(s0, z, 1.0)];
+/// let copy_gate_external_magnetic_field = vec![0.0, 0.0];
+/// let wang_landau_configuration = WangLandauConfiguration {
+///     final_log_modification_factor: 1e-4,
+///     ..Default::default()
+/// };
+///
+/// let result = wang_landau(
+///     &copy_gate_interactions,
+///     &copy_gate_external_magnetic_field,
+///     Some(&wang_landau_configuration),
+/// );
+///
+/// assert!(result.converged);
+/// assert_eq!(vec![-1.0, 1.0], result.density_of_states.energies);
+/// assert!((result.density_of_states.ground_state_degeneracy() - 2.0).abs() < 0.1);
+/// ```
+pub fn wang_landau(
+    interactions: &Interactions,
+    external_magnetic_field: &ExternalMagneticField,
+    configuration_override: Option<&WangLandauConfiguration>,
+) -> WangLandauResult {
+    let config = configuration_override.cloned().unwrap_or_default();
+    let bin_width = config.bin_width.unwrap_or(ENERGY_RESOLUTION);
+    assert!(bin_width > 0.0, "The bin width has to be positive");
+    let bin_of = |energy: Energy| (energy / bin_width).round() as i64;
+
+    let n = external_magnetic_field.len();
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let initial_state: State = (0..n).map(|_| rng.gen::<bool>()).collect();
+    let mut two_local_hamiltonian = TwoLocalHamiltonian::new(
+        interactions.clone(),
+        external_magnetic_field.clone(),
+        Some(initial_state),
+    );
+    // The bin of the walk is kept as the one every accepted move was binned into, rather than recomputed from the
+    // energy, which is summed anew and could round into a neighbouring bin
+    let mut current_bin = bin_of(two_local_hamiltonian.current_energy());
+    let mut bins: HashMap<i64, Bin> = HashMap::from([(
+        current_bin,
+        Bin {
+            energy: two_local_hamiltonian.current_energy(),
+            log_density_of_states: 0.0,
+            visits: 0,
+        },
+    )]);
+    let mut log_modification_factor = 1.0;
+    let mut sweeps = 0;
+    let mut steps = 0;
+    let mut one_over_t = false;
+
+    while n > 0
+        && log_modification_factor > config.final_log_modification_factor
+        && sweeps < config.max_sweeps
+    {
+        for _ in 0..n * config.sweeps_per_check.max(1) {
+            let spin = rng.gen_range(0..n);
+            let new_energy =
+                two_local_hamiltonian.current_energy() + two_local_hamiltonian.delta_energy(spin);
+            let new_bin = bin_of(new_energy);
+            if !bins.contains_key(&new_bin) {
+                let lowest_log_density_of_states = bins
+                    .values()
+                    .map(|bin| bin.log_density_of_states)
+                    .fold(f64::INFINITY, f64::min);
+                bins.insert(
+                    new_bin,
+                    Bin {
+                        energy: new_energy,
+                        log_density_of_states: lowest_log_density_of_states,
+                        visits: 0,
+                    },
+                );
+            }
+            let log_acceptance =
+                bins[&current_bin].log_density_of_states - bins[&new_bin].log_density_of_states;
+            if log_acceptance >= 0.0 || rng.gen::<f64>().ln() < log_acceptance {
+                two_local_hamiltonian.flip_spin(spin);
+                current_bin = new_bin;
+            }
+
+            steps += 1;
+            if one_over_t {
+                log_modification_factor = bins.len() as f64 / steps as f64;
+            }
+            let bin = bins.get_mut(&current_bin).unwrap();
+            bin.energy = bin.energy.min(two_local_hamiltonian.current_energy());
+            bin.log_density_of_states += log_modification_factor;
+            bin.visits += 1;
+        }
+        sweeps += config.sweeps_per_check.max(1);
+
+        let mean_visits =
+            bins.values().map(|bin| bin.visits).sum::<usize>() as f32 / bins.len() as f32;
+        if !one_over_t
+            && bins
+                .values()
+                .all(|bin| bin.visits as f32 >= config.flatness * mean_visits)
+        {
+            log_modification_factor /= 2.0;
+            for bin in bins.values_mut() {
+                bin.visits = 0;
+            }
+            one_over_t = log_modification_factor < bins.len() as f64 / steps as f64;
+        }
+    }
+
+    let mut bins: Vec<Bin> = bins.into_values().collect();
+    bins.sort_by(|left, right| left.energy.total_cmp(&right.energy));
+    let mut density_of_states = DensityOfStates {
+        energies: bins.iter().map(|bin| bin.energy).collect(),
+        log_degeneracies: bins.iter().map(|bin| bin.log_density_of_states).collect(),
+    };
+    // The walk only knows the density of states up to a constant factor, which is fixed by the total number of states
+    let normalization =
+        n as f64 * std::f64::consts::LN_2 - density_of_states.log_partition_function(0.0);
+    for log_degeneracy in &mut density_of_states.log_degeneracies {
+        *log_degeneracy += normalization;
+    }
+
+    WangLandauResult {
+        density_of_states,
+        sweeps,
+        converged: log_modification_factor <= config.final_log_modification_factor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::edwards_anderson_2d;
+    use crate::lattice::CouplingDistribution;
+    use crate::solvers::partition_function;
+    use crate::solvers::wang_landau::{wang_landau, WangLandauConfiguration};
+
+    #[test]
+    fn test_wang_landau_matches_the_exact_density_of_states() {
+        let (interactions, external_magnetic_field) =
+            edwards_anderson_2d(4, 4, CouplingDistribution::PlusMinusJ, 7);
+        let exact_density_of_states =
+            partition_function(&interactions, &external_magnetic_field, 1.0).density_of_states;
+        let wang_landau_configuration = WangLandauConfiguration {
+            final_log_modification_factor: 1e-5,
+            ..Default::default()
+        };
+
+        let result = wang_landau(
+            &interactions,
+            &external_magnetic_field,
+            Some(&wang_landau_configuration),
+        );
+
+        assert!(result.converged);
+        let density_of_states = result.density_of_states;
+        assert_eq!(exact_density_of_states.energies, density_of_states.energies);
+        for (log_degeneracy, exact_log_degeneracy) in density_of_states
+            .log_degeneracies
+            .iter()
+            .zip(&exact_density_of_states.log_degeneracies)
+        {
+            assert!((log_degeneracy - exact_log_degeneracy).abs() < 0.1);
+        }
+        for beta in [0.1, 0.5, 2.0] {
+            let exact_mean_energy = exact_density_of_states.mean_energy(beta);
+            assert!((density_of_states.mean_energy(beta) - exact_mean_energy).abs() < 0.1);
+        }
+    }
+}
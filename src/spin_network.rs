@@ -1,10 +1,31 @@
+use crate::embedding::{
+    find_embedding, verify_embedding, EmbeddedSpinNetwork, Embedding, HardwareGraph,
+    MinorEmbeddingConfiguration,
+};
+use crate::gauge::apply_gauge;
+use crate::lattice::SquareLattice;
+use crate::sample_set::SampleSet;
+use crate::solvers::block_moves::LogicalBlock;
+use crate::solvers::boltzmann_sampling::{sample_boltzmann, BoltzmannSamplingConfiguration};
+use crate::solvers::exact_quantum_annealing::{
+    exact_quantum_annealing, ExactQuantumAnnealingConfiguration, QuantumEvolution,
+};
+use crate::solvers::simulated_quantum_annealing::{
+    simulated_quantum_annealing, SimulatedQuantumAnnealingConfiguration,
+};
+use crate::solvers::tabu_search::{tabu_search, TabuSearchConfiguration};
+use crate::solvers::wang_landau::{wang_landau, WangLandauConfiguration, WangLandauResult};
 use crate::solvers::{
-    find_all_ground_states, simulated_annealing, Epoch, SimulatedAnnealingConfiguration,
+    count_ground_states, find_all_ground_states, partition_function, simulated_annealing,
+    GroundStateCount, GroundStateCountingConfiguration, PartitionFunction,
+    SimulatedAnnealingConfiguration,
 };
 use crate::types::{
-    BinaryNode, Energy, ExternalMagneticField, Interactions, MagneticFieldStrength, SpinIndex,
-    State, UnaryNode
+    BinaryNode, ExternalMagneticField, GridPosition, InteractionStrength, Interactions,
+    MagneticFieldStrength, Qubit, SpinIndex, UnaryNode,
 };
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// A SpinNetwork is meant to represent a 2D Spin Glass.
 /// It provides methods to add any number of nodes with one, two, or n inputs, and one output.
@@ -13,6 +34,8 @@ pub struct SpinNetwork {
     input_nodes: Vec<SpinIndex>,
     auxiliary_nodes: Vec<SpinIndex>,
     output_nodes: Vec<SpinIndex>,
+    logical_blocks: Vec<LogicalBlock>,
+    positions: Vec<Option<GridPosition>>,
     pub interactions: Interactions,
     pub external_magnetic_field: ExternalMagneticField,
 }
@@ -20,10 +43,11 @@ pub struct SpinNetwork {
 impl SpinNetwork {
     /// Creates a new SpinNetwork with no nodes, interactions or external magnetic field
     pub fn new() -> Self {
-        return Default::default();
+        Default::default()
     }
     fn add_free_node(&mut self) -> usize {
         self.external_magnetic_field.push(0.0);
+        self.positions.push(None);
 
         self.external_magnetic_field.len() - 1
     }
@@ -78,7 +102,11 @@ impl SpinNetwork {
     /// spin_network.add_unary_node(s0, &copy_gate);
     /// ```
     pub fn add_unary_node(&mut self, input: usize, unary_node: &impl UnaryNode) -> usize {
-        return UnaryNode::connect_to_one(unary_node, self, input);
+        let first_spin_index = self.external_magnetic_field.len();
+        let output_node_index = UnaryNode::connect_to_one(unary_node, self, input);
+        self.add_logical_block(vec![input], first_spin_index);
+
+        output_node_index
     }
     /// Adds a Node with two inputs and one output. It returns the index of the output node.
     ///
@@ -101,7 +129,88 @@ impl SpinNetwork {
         right_input: usize,
         binary_node: &impl BinaryNode,
     ) -> usize {
-        return BinaryNode::connect_to_two(binary_node, self, left_input, right_input);
+        let first_spin_index = self.external_magnetic_field.len();
+        let output_node_index =
+            BinaryNode::connect_to_two(binary_node, self, left_input, right_input);
+        self.add_logical_block(vec![left_input, right_input], first_spin_index);
+
+        output_node_index
+    }
+    /// Records the spins from `first_spin_index` onwards as a block fed by `inputs`. The blocks of the nodes that a
+    /// node adds while connecting itself, like the COPY gates of an AND gate, are merged into its own block. Nodes that
+    /// don't add any spin, and only couple existing ones, have no block.
+    fn add_logical_block(&mut self, inputs: Vec<SpinIndex>, first_spin_index: SpinIndex) {
+        if first_spin_index == self.external_magnetic_field.len() {
+            return;
+        }
+        self.logical_blocks.retain(|logical_block| {
+            logical_block
+                .spins
+                .first()
+                .is_some_and(|spin| *spin < first_spin_index)
+        });
+        self.logical_blocks.push(LogicalBlock {
+            inputs,
+            spins: (first_spin_index..self.external_magnetic_field.len()).collect(),
+        });
+    }
+    /// Returns the nodes added with [SpinNetwork::add_unary_node] and [SpinNetwork::add_binary_node] as
+    /// [LogicalBlock]s, in the order in which they were added. Passing them to [SimulatedAnnealingConfiguration]
+    /// enables block moves, which flip an input of the network and every spin that depends on it at once.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    /// use ernst::solvers::SimulatedAnnealingConfiguration;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+    ///
+    /// let logical_blocks = spin_network.logical_blocks();
+    /// assert_eq!(1, logical_blocks.len());
+    /// assert_eq!(vec![s0, s1], logical_blocks[0].inputs);
+    ///
+    /// let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+    ///     logical_blocks,
+    ///     ..Default::default()
+    /// };
+    /// let sample_set = spin_network.run_simulated_annealing(Some(&simulated_annealing_configuration), Some(vec![s0, s1, z]));
+    ///
+    /// assert_eq!(vec![true, true, true], sample_set.lowest().samples[0].state);
+    /// ```
+    pub fn logical_blocks(&self) -> Vec<LogicalBlock> {
+        self.logical_blocks.clone()
+    }
+    /// Applies the spin reversal transformation `gauge` to the SpinNetwork, flipping the signs of the external
+    /// magnetic field of every spin for which `gauge` is true and of the interactions between a flipped and an unflipped
+    /// spin. The spectrum doesn't change, and samples are mapped back to the original SpinNetwork with
+    /// [SampleSet::apply_gauge]. Applying the same gauge again undoes it. See [crate::gauge] for more details.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    /// use ernst::gauge::random_gauge;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+    /// let gauge = random_gauge(spin_network.external_magnetic_field.len(), 7);
+    ///
+    /// spin_network.apply_gauge(&gauge);
+    /// let sample_set = spin_network.find_all_ground_states(None).apply_gauge(&gauge);
+    ///
+    /// assert_eq!(vec![true, true, true], sample_set.project(&[s0, s1, z]).samples[0].state);
+    /// ```
+    pub fn apply_gauge(&mut self, gauge: &[bool]) {
+        (self.interactions, self.external_magnetic_field) =
+            apply_gauge(&self.interactions, &self.external_magnetic_field, gauge);
     }
     /// Finds all ground states of the spin glass represented by the SpinNetwork. The argument `spin_ordering`, when
     /// given, will ensure that the `State`s will be projected according to it.
@@ -135,28 +244,110 @@ impl SpinNetwork {
     ///    (-7.0, vec![false, false, true, true]),
     /// ];
     ///
-    /// assert_eq!(expected_ground_states, actual_ground_states)
+    /// assert_eq!(expected_ground_states, actual_ground_states.energies_and_states())
     /// ```
     pub fn find_all_ground_states(
         &self,
         spin_ordering: Option<Vec<SpinIndex>>,
-    ) -> Vec<(Energy, State)> {
-        return find_all_ground_states(&self.interactions, &self.external_magnetic_field)
-            .into_iter()
-            .map(|(energy, state)| {
-                if let Some(spin_ordering) = &spin_ordering {
-                    return (
-                        energy,
-                        spin_ordering
-                            .iter()
-                            .map(|spin_index| state[*spin_index])
-                            .collect(),
-                    );
-                }
+    ) -> SampleSet {
+        let sample_set = find_all_ground_states(&self.interactions, &self.external_magnetic_field);
+        match spin_ordering {
+            Some(spin_ordering) => sample_set.project(&spin_ordering),
+            None => sample_set,
+        }
+    }
+    /// Computes the partition function of the spin glass represented by the SpinNetwork at inverse temperature `beta`,
+    /// along with its ground states and density of states. See [partition_function] for more details.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(0.0);
+    /// let s1 = spin_network.add_input_node(0.0);
+    /// spin_network.add_binary_node(s0, s1, &AND::default());
+    ///
+    /// let partition_function = spin_network.partition_function(2.0);
+    ///
+    /// // Every row of the truth table is a ground state
+    /// assert_eq!(4, partition_function.ground_states.len());
+    /// assert_eq!(4.0, partition_function.density_of_states.degeneracies()[0].round());
+    /// ```
+    pub fn partition_function(&self, beta: f32) -> PartitionFunction {
+        partition_function(&self.interactions, &self.external_magnetic_field, beta)
+    }
+    /// Counts the ground states of the spin glass represented by the SpinNetwork, along with a uniform random sample of
+    /// them, which is projected onto `spin_ordering` if given. See [count_ground_states] for more details.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(0.0);
+    /// let s1 = spin_network.add_input_node(0.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+    ///
+    /// let ground_state_count = spin_network.count_ground_states(None, Some(vec![s0, s1, z]));
+    ///
+    /// // Every row of the truth table is a ground state
+    /// assert_eq!(4, ground_state_count.count);
+    /// assert!(ground_state_count.samples.iter().all(|sample| sample.state[2] == (sample.state[0] && sample.state[1])));
+    /// ```
+    pub fn count_ground_states(
+        &self,
+        configuration_override: Option<&GroundStateCountingConfiguration>,
+        spin_ordering: Option<Vec<SpinIndex>>,
+    ) -> GroundStateCount {
+        let mut ground_state_count = count_ground_states(
+            &self.interactions,
+            &self.external_magnetic_field,
+            configuration_override,
+        );
+        if let Some(spin_ordering) = spin_ordering {
+            ground_state_count.samples = ground_state_count.samples.project(&spin_ordering);
+        }
 
-                return (energy, state);
-            })
-            .collect();
+        ground_state_count
+    }
+    /// Estimates the density of states of the spin glass represented by the SpinNetwork with Wang-Landau sampling. See
+    /// [wang_landau] for more details.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    /// use ernst::solvers::wang_landau::WangLandauConfiguration;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(0.0);
+    /// let s1 = spin_network.add_input_node(0.0);
+    /// spin_network.add_binary_node(s0, s1, &AND::default());
+    /// let wang_landau_configuration = WangLandauConfiguration {
+    ///     final_log_modification_factor: 1e-5,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let result = spin_network.run_wang_landau(Some(&wang_landau_configuration));
+    ///
+    /// // Every row of the truth table is a ground state
+    /// assert!((result.density_of_states.ground_state_degeneracy() - 4.0).abs() < 0.5);
+    /// ```
+    pub fn run_wang_landau(
+        &self,
+        configuration_override: Option<&WangLandauConfiguration>,
+    ) -> WangLandauResult {
+        wang_landau(
+            &self.interactions,
+            &self.external_magnetic_field,
+            configuration_override,
+        )
     }
     /// Explores the energy landscape of the spin glass represented by the SpinNetwork. The argument `spin_ordering`, when
     /// given, will ensure that the `State`s will be projected according
@@ -165,8 +356,10 @@ impl SpinNetwork {
     /// ### Example
     ///
     /// ```
+    /// use std::collections::HashSet;
     /// use ernst::spin_network::SpinNetwork;
     /// use ernst::nodelib::logic_gates::OR;
+    /// use ernst::solvers::SimulatedAnnealingConfiguration;
     ///
     /// let mut spin_network = SpinNetwork::new();
     /// let s0 = spin_network.add_input_node(0.0);
@@ -177,19 +370,31 @@ impl SpinNetwork {
     /// let z_aux = spin_network.add_binary_node(s0, s1, &or_gate);
     /// let z = spin_network.add_binary_node(z_aux, s2, &or_gate);
     ///
+    /// // Every read ends up in one of the 8 degenerate ground states, so we need enough of them to see all
+    /// let simulated_annealing_configuration = SimulatedAnnealingConfiguration {
+    ///     num_reads: 100,
+    ///     ..Default::default()
+    /// };
+    ///
     /// // Note how we only ask for ground states to be ordered according to the "interesting" spins i.e
     /// // the ones that are able to
-    /// let actual_ground_states = spin_network.run_simulated_annealing(None, Some(vec![s0, s1, s2, z]));
-    /// let expected_ground_states = vec![
-    ///    (-7.0, vec![false, false, false, false]),
-    ///    (-7.0, vec![true, false, false, true]),
-    ///    (-7.0, vec![true, true, false, true]),
-    ///    (-7.0, vec![false, true, false, true]),
-    ///    (-7.0, vec![false, true, true, true]),
-    ///    (-7.0, vec![true, true, true, true]),
-    ///    (-7.0, vec![true, false, true, true]),
-    ///    (-7.0, vec![false, false, true, true]),
-    /// ];
+    /// let actual_ground_states: HashSet<_> = spin_network
+    ///    .run_simulated_annealing(Some(&simulated_annealing_configuration), Some(vec![s0, s1, s2, z]))
+    ///    .into_iter()
+    ///    .map(|sample| sample.state)
+    ///    .collect();
+    /// let expected_ground_states: HashSet<_> = vec![
+    ///    vec![false, false, false, false],
+    ///    vec![true, false, false, true],
+    ///    vec![true, true, false, true],
+    ///    vec![false, true, false, true],
+    ///    vec![false, true, true, true],
+    ///    vec![true, true, true, true],
+    ///    vec![true, false, true, true],
+    ///    vec![false, false, true, true],
+    /// ]
+    /// .into_iter()
+    /// .collect();
     ///
     /// assert_eq!(expected_ground_states, actual_ground_states)
     /// ```
@@ -197,37 +402,608 @@ impl SpinNetwork {
         &self,
         configuration_override: Option<&SimulatedAnnealingConfiguration>,
         spin_ordering: Option<Vec<SpinIndex>>,
-    ) -> Vec<(Energy, State, Epoch)> {
-        return simulated_annealing(
+    ) -> SampleSet {
+        let sample_set = simulated_annealing(
             &self.interactions,
             &self.external_magnetic_field,
             configuration_override,
+        );
+        match spin_ordering {
+            Some(spin_ordering) => sample_set.project(&spin_ordering),
+            None => sample_set,
+        }
+    }
+    /// Draws samples from the Boltzmann distribution at inverse temperature `beta` of the spin glass represented by the
+    /// SpinNetwork. See [sample_boltzmann] for more details. The argument `spin_ordering`, when given, will ensure
+    /// that the `State`s will be projected according to it.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    /// use ernst::observables::magnetization;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+    ///
+    /// let sample_set = spin_network.sample_boltzmann(5.0, None, Some(vec![s0, s1, z]));
+    ///
+    /// // At low temperature, the circuit is almost always in its only ground state
+    /// assert!(magnetization(&sample_set) > 0.95);
+    /// ```
+    pub fn sample_boltzmann(
+        &self,
+        beta: f32,
+        configuration_override: Option<&BoltzmannSamplingConfiguration>,
+        spin_ordering: Option<Vec<SpinIndex>>,
+    ) -> SampleSet {
+        let sample_set = sample_boltzmann(
+            &self.interactions,
+            &self.external_magnetic_field,
+            beta,
+            configuration_override,
+        );
+        match spin_ordering {
+            Some(spin_ordering) => sample_set.project(&spin_ordering),
+            None => sample_set,
+        }
+    }
+    /// Simulates quantum annealing of the spin glass represented by the SpinNetwork with path-integral Monte Carlo.
+    /// See [simulated_quantum_annealing] for more details. The argument `spin_ordering`, when given, will ensure that
+    /// the `State`s will be projected according to it.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+    ///
+    /// let sample_set = spin_network.run_simulated_quantum_annealing(None, Some(vec![s0, s1, z]));
+    ///
+    /// assert_eq!(vec![true, true, true], sample_set.lowest().samples[0].state);
+    /// ```
+    pub fn run_simulated_quantum_annealing(
+        &self,
+        configuration_override: Option<&SimulatedQuantumAnnealingConfiguration>,
+        spin_ordering: Option<Vec<SpinIndex>>,
+    ) -> SampleSet {
+        let sample_set = simulated_quantum_annealing(
+            &self.interactions,
+            &self.external_magnetic_field,
+            configuration_override,
+        );
+        match spin_ordering {
+            Some(spin_ordering) => sample_set.project(&spin_ordering),
+            None => sample_set,
+        }
+    }
+    /// Searches for the ground states of the spin glass represented by the SpinNetwork with tabu search. See
+    /// [tabu_search] for more details. The argument `spin_ordering`, when given, will ensure that the `State`s will be
+    /// projected according to it.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::XOR;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(-1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &XOR::default());
+    ///
+    /// let sample_set = spin_network.run_tabu_search(None, Some(vec![s0, s1, z]));
+    ///
+    /// assert_eq!(vec![true, false, true], sample_set.lowest().samples[0].state);
+    /// ```
+    pub fn run_tabu_search(
+        &self,
+        configuration_override: Option<&TabuSearchConfiguration>,
+        spin_ordering: Option<Vec<SpinIndex>>,
+    ) -> SampleSet {
+        let sample_set = tabu_search(
+            &self.interactions,
+            &self.external_magnetic_field,
+            configuration_override,
+        );
+        match spin_ordering {
+            Some(spin_ordering) => sample_set.project(&spin_ordering),
+            None => sample_set,
+        }
+    }
+    /// Simulates quantum annealing of the spin glass represented by the SpinNetwork exactly, by state vector time
+    /// evolution. See [exact_quantum_annealing] for more details.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::AND;
+    /// use ernst::solvers::exact_quantum_annealing::ExactQuantumAnnealingConfiguration;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &AND::default());
+    /// let exact_quantum_annealing_configuration = ExactQuantumAnnealingConfiguration {
+    ///     annealing_time: 30.0,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let quantum_evolution = spin_network.run_exact_quantum_annealing(Some(&exact_quantum_annealing_configuration));
+    ///
+    /// // The probability of reading both inputs and the output as true, whatever the auxiliary spins are
+    /// let all_true = (1 << s0) | (1 << s1) | (1 << z);
+    /// let probability: f32 = quantum_evolution
+    ///     .probabilities
+    ///     .iter()
+    ///     .enumerate()
+    ///     .filter(|(state, _)| state & all_true == all_true)
+    ///     .map(|(_, probability)| probability)
+    ///     .sum();
+    /// assert!(quantum_evolution.ground_state_probability > 0.95);
+    /// assert!(probability > 0.95);
+    /// ```
+    pub fn run_exact_quantum_annealing(
+        &self,
+        configuration_override: Option<&ExactQuantumAnnealingConfiguration>,
+    ) -> QuantumEvolution {
+        exact_quantum_annealing(
+            &self.interactions,
+            &self.external_magnetic_field,
+            configuration_override,
+        )
+    }
+    /// Heuristically finds a minor embedding of the spin glass represented by the SpinNetwork onto `hardware_graph`.
+    /// See [find_embedding] for more details.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::nodelib::logic_gates::XOR;
+    /// use ernst::embedding::topologies::chimera_graph;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(0.0);
+    /// let s1 = spin_network.add_input_node(0.0);
+    /// spin_network.add_binary_node(s0, s1, &XOR::default());
+    ///
+    /// let embedding = spin_network.find_embedding(&chimera_graph(2, 2, 4), None).unwrap();
+    ///
+    /// assert_eq!(spin_network.external_magnetic_field.len(), embedding.chains.len());
+    /// ```
+    pub fn find_embedding(
+        &self,
+        hardware_graph: &HardwareGraph,
+        configuration_override: Option<&MinorEmbeddingConfiguration>,
+    ) -> Option<Embedding> {
+        find_embedding(
+            &self.interactions,
+            self.external_magnetic_field.len(),
+            hardware_graph,
+            configuration_override,
         )
-        .into_iter()
-        .map(|(energy, state, epoch)| {
-            if let Some(spin_ordering) = &spin_ordering {
-                return (
-                    energy,
-                    spin_ordering
-                        .iter()
-                        .map(|spin_index| state[*spin_index])
-                        .collect(),
-                    epoch,
-                );
+    }
+    /// Places the spin `spin_index` at `position` of a 2D grid, replacing any previous placement.
+    pub fn place_spin(&mut self, spin_index: SpinIndex, position: GridPosition) {
+        if self.positions.len() <= spin_index {
+            self.positions.resize(spin_index + 1, None);
+        }
+        self.positions[spin_index] = Some(position);
+    }
+    /// Returns where the spin `spin_index` was placed, if it was.
+    pub fn position(&self, spin_index: SpinIndex) -> Option<GridPosition> {
+        self.positions.get(spin_index).copied().flatten()
+    }
+    /// Checks whether the SpinNetwork natively fits `lattice`. That is, every spin is placed on its own site of the
+    /// lattice, and every interaction is between nearest neighbours.
+    pub fn has_only_nearest_neighbour_couplings(&self, lattice: &SquareLattice) -> bool {
+        let mut occupied_sites = HashSet::new();
+        for spin_index in 0..self.external_magnetic_field.len() {
+            match self.position(spin_index) {
+                Some(position) if lattice.contains(position) && occupied_sites.insert(position) => {
+                }
+                _ => return false,
             }
+        }
 
-            (energy, state, epoch)
+        self.interactions
+            .iter()
+            .all(|(i, j, _)| match (self.position(*i), self.position(*j)) {
+                (Some(left_position), Some(right_position)) => {
+                    lattice.are_neighbours(left_position, right_position)
+                }
+                _ => false,
+            })
+    }
+    /// Places the SpinNetwork onto `lattice`, so that it only has nearest-neighbour interactions. Spins are laid out
+    /// three sites apart from each other, and every interaction between spins that aren't neighbours is routed
+    /// through a chain of COPY spins, ferromagnetically coupled with `chain_strength`, that extends one of them.
+    ///
+    /// The spin `i` of the SpinNetwork is the spin `i` of the returned one, and the COPY spins are added as auxiliary
+    /// nodes after them. It returns `None` if the lattice is too small, or too crowded, for every interaction to be
+    /// routed. Keep in mind that a chain is only as reliable as its `chain_strength` is large, when compared to the
+    /// interactions of the spin that it extends.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::lattice::SquareLattice;
+    /// use ernst::nodelib::logic_gates::OR;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(0.0);
+    /// let s1 = spin_network.add_input_node(0.0);
+    /// spin_network.add_binary_node(s0, s1, &OR::default());
+    ///
+    /// let lattice = SquareLattice::new(8, 8);
+    /// let placed_spin_network = spin_network.place_on_grid(&lattice, 2.0).unwrap();
+    ///
+    /// assert!(!spin_network.has_only_nearest_neighbour_couplings(&lattice));
+    /// assert!(placed_spin_network.has_only_nearest_neighbour_couplings(&lattice));
+    /// ```
+    pub fn place_on_grid(
+        &self,
+        lattice: &SquareLattice,
+        chain_strength: InteractionStrength,
+    ) -> Option<SpinNetwork> {
+        let n = self.external_magnetic_field.len();
+        let mut neighbours: Vec<Vec<SpinIndex>> = vec![vec![]; n];
+        let mut logical_interactions: IndexMap<(SpinIndex, SpinIndex), InteractionStrength> =
+            IndexMap::new();
+        for (i, j, interaction_strength) in &self.interactions {
+            if i != j {
+                *logical_interactions
+                    .entry((*i.min(j), *i.max(j)))
+                    .or_default() += interaction_strength;
+                neighbours[*i].push(*j);
+                neighbours[*j].push(*i);
+            }
+        }
+
+        // Sites are visited in a serpentine order, and spins in breadth-first order, so that neighbouring spins end up
+        // close to each other
+        let mut sites: Vec<GridPosition> = vec![];
+        for (row, y) in (1..lattice.height).step_by(3).enumerate() {
+            let mut row_sites: Vec<GridPosition> =
+                (1..lattice.width).step_by(3).map(|x| (x, y)).collect();
+            if row % 2 == 1 {
+                row_sites.reverse();
+            }
+            sites.extend(row_sites);
+        }
+        if sites.len() < n {
+            return None;
+        }
+        let mut order: Vec<SpinIndex> = Vec::with_capacity(n);
+        let mut queued = vec![false; n];
+        for root in 0..n {
+            if queued[root] {
+                continue;
+            }
+            queued[root] = true;
+            let mut queue = VecDeque::from([root]);
+            while let Some(spin_index) = queue.pop_front() {
+                order.push(spin_index);
+                for neighbour in &neighbours[spin_index] {
+                    if !queued[*neighbour] {
+                        queued[*neighbour] = true;
+                        queue.push_back(*neighbour);
+                    }
+                }
+            }
+        }
+
+        let mut placed_spin_network = SpinNetwork::new();
+        let mut occupants: HashMap<GridPosition, (SpinIndex, SpinIndex)> = HashMap::new();
+        let mut chains: Vec<Vec<GridPosition>> = vec![vec![]; n];
+        for spin_index in 0..n {
+            placed_spin_network.add_node_like(
+                self,
+                spin_index,
+                self.external_magnetic_field[spin_index],
+            );
+        }
+        for (spin_index, site) in order.into_iter().zip(sites) {
+            placed_spin_network.place_spin(spin_index, site);
+            occupants.insert(site, (spin_index, spin_index));
+            chains[spin_index].push(site);
+        }
+
+        for ((i, j), interaction_strength) in logical_interactions {
+            if interaction_strength == 0.0 {
+                continue;
+            }
+            let is_target = |position: &GridPosition| -> Option<SpinIndex> {
+                lattice
+                    .neighbours(*position)
+                    .into_iter()
+                    .find_map(|neighbour| {
+                        occupants
+                            .get(&neighbour)
+                            .filter(|(owner, _)| *owner == j)
+                            .map(|(_, placed_spin_index)| *placed_spin_index)
+                    })
+            };
+
+            if let Some((from, to)) = chains[i]
+                .iter()
+                .find_map(|site| is_target(site).map(|to| (occupants[site].1, to)))
+            {
+                placed_spin_network
+                    .interactions
+                    .push((from, to, interaction_strength));
+                continue;
+            }
+
+            let mut predecessors: HashMap<GridPosition, GridPosition> = HashMap::new();
+            let mut queue: VecDeque<GridPosition> = chains[i].iter().cloned().collect();
+            let mut found = None;
+            'search: while let Some(site) = queue.pop_front() {
+                for neighbour in lattice.neighbours(site) {
+                    if occupants.contains_key(&neighbour) || predecessors.contains_key(&neighbour) {
+                        continue;
+                    }
+                    predecessors.insert(neighbour, site);
+                    if let Some(to) = is_target(&neighbour) {
+                        found = Some((neighbour, to));
+                        break 'search;
+                    }
+                    queue.push_back(neighbour);
+                }
+            }
+            let (last_site, to) = found?;
+
+            let mut path = vec![last_site];
+            while let Some(predecessor) = predecessors.get(path.last().unwrap()) {
+                path.push(*predecessor);
+            }
+            path.reverse();
+            let mut from = occupants[&path[0]].1;
+            for site in path.into_iter().skip(1) {
+                let copy_spin_index = placed_spin_network.add_auxiliary_node(0.0);
+                placed_spin_network.place_spin(copy_spin_index, site);
+                placed_spin_network
+                    .interactions
+                    .push((from, copy_spin_index, chain_strength));
+                occupants.insert(site, (i, copy_spin_index));
+                chains[i].push(site);
+                from = copy_spin_index;
+            }
+            placed_spin_network
+                .interactions
+                .push((from, to, interaction_strength));
+        }
+
+        Some(placed_spin_network)
+    }
+    /// Adds a node with the same role, input, auxiliary or output, as the node `spin_index` of `spin_network`.
+    fn add_node_like(
+        &mut self,
+        spin_network: &SpinNetwork,
+        spin_index: SpinIndex,
+        magnetic_field_strength: MagneticFieldStrength,
+    ) -> SpinIndex {
+        if spin_network.input_nodes.contains(&spin_index) {
+            self.add_input_node(magnetic_field_strength)
+        } else if spin_network.output_nodes.contains(&spin_index) {
+            self.add_output_node(magnetic_field_strength)
+        } else {
+            self.add_auxiliary_node(magnetic_field_strength)
+        }
+    }
+    /// Embeds the spin glass represented by the SpinNetwork according to `embedding`. The external magnetic field of
+    /// every spin is evenly split across its chain, every coupler within a chain gets a ferromagnetic interaction of
+    /// `chain_strength`, and every interaction is evenly split across the couplers between the two chains.
+    ///
+    /// Returns None if `embedding` isn't valid for this spin glass, as checked by
+    /// [crate::embedding::verify_embedding] against the couplers it keeps, e.g. if there's no coupler between the
+    /// chains of two interacting spins.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use ernst::spin_network::SpinNetwork;
+    /// use ernst::embedding::ChainBreakResolution;
+    /// use ernst::embedding::topologies::chimera_graph;
+    /// use ernst::nodelib::logic_gates::XOR;
+    ///
+    /// let mut spin_network = SpinNetwork::new();
+    /// let s0 = spin_network.add_input_node(1.0);
+    /// let s1 = spin_network.add_input_node(-1.0);
+    /// let z = spin_network.add_binary_node(s0, s1, &XOR::default());
+    ///
+    /// let embedding = spin_network.find_embedding(&chimera_graph(2, 2, 4), None).unwrap();
+    /// let embedded_spin_network = spin_network.embed(&embedding, 2.0).unwrap();
+    ///
+    /// // Pretend that the annealer returned the physical ground state
+    /// let physical_ground_states = embedded_spin_network.spin_network.find_all_ground_states(None);
+    /// let logical_sample = embedded_spin_network
+    ///     .unembed(&physical_ground_states, ChainBreakResolution::MajorityVote)
+    ///     .samples
+    ///     .remove(0);
+    /// let logical_state = logical_sample.state;
+    ///
+    /// assert_eq!(vec![true, false, true], vec![logical_state[s0], logical_state[s1], logical_state[z]]);
+    /// assert_eq!(Some(0.0), logical_sample.chain_break_fraction);
+    /// ```
+    pub fn embed(
+        &self,
+        embedding: &Embedding,
+        chain_strength: InteractionStrength,
+    ) -> Option<EmbeddedSpinNetwork> {
+        let num_qubits = embedding
+            .chains
+            .iter()
+            .flatten()
+            .chain(
+                embedding
+                    .couplers
+                    .iter()
+                    .flat_map(|(left_qubit, right_qubit)| [left_qubit, right_qubit]),
+            )
+            .max()
+            .map_or(0, |qubit| qubit + 1);
+        let hardware_graph = HardwareGraph::new(num_qubits, embedding.couplers.clone());
+        if !verify_embedding(
+            &self.interactions,
+            self.external_magnetic_field.len(),
+            embedding,
+            &hardware_graph,
+        ) {
+            return None;
+        }
+        let mut physical_spin_network = SpinNetwork::new();
+        let mut qubits: Vec<Qubit> = vec![];
+        let mut chains: Vec<Vec<SpinIndex>> = vec![];
+        let mut owners: HashMap<Qubit, (SpinIndex, SpinIndex)> = HashMap::new();
+
+        for (spin_index, chain) in embedding.chains.iter().enumerate() {
+            let magnetic_field_strength =
+                self.external_magnetic_field[spin_index] / chain.len() as MagneticFieldStrength;
+            let physical_chain = chain
+                .iter()
+                .map(|qubit| {
+                    let physical_spin_index = physical_spin_network.add_node_like(
+                        self,
+                        spin_index,
+                        magnetic_field_strength,
+                    );
+                    qubits.push(*qubit);
+                    owners.insert(*qubit, (spin_index, physical_spin_index));
+
+                    physical_spin_index
+                })
+                .collect();
+            chains.push(physical_chain);
+        }
+
+        let mut couplers_between_chains: HashMap<
+            (SpinIndex, SpinIndex),
+            Vec<(SpinIndex, SpinIndex)>,
+        > = HashMap::new();
+        for (left_qubit, right_qubit) in &embedding.couplers {
+            if let (
+                Some((left_spin_index, left_physical)),
+                Some((right_spin_index, right_physical)),
+            ) = (owners.get(left_qubit), owners.get(right_qubit))
+            {
+                if left_spin_index == right_spin_index {
+                    physical_spin_network.interactions.push((
+                        *left_physical,
+                        *right_physical,
+                        chain_strength,
+                    ));
+                } else if left_spin_index < right_spin_index {
+                    couplers_between_chains
+                        .entry((*left_spin_index, *right_spin_index))
+                        .or_default()
+                        .push((*left_physical, *right_physical));
+                } else {
+                    couplers_between_chains
+                        .entry((*right_spin_index, *left_spin_index))
+                        .or_default()
+                        .push((*right_physical, *left_physical));
+                }
+            }
+        }
+
+        let mut logical_interactions: HashMap<(SpinIndex, SpinIndex), InteractionStrength> =
+            HashMap::new();
+        for (i, j, interaction_strength) in &self.interactions {
+            if i != j {
+                *logical_interactions
+                    .entry((*i.min(j), *i.max(j)))
+                    .or_default() += interaction_strength;
+            }
+        }
+        let mut logical_interactions: Vec<_> = logical_interactions.into_iter().collect();
+        logical_interactions.sort_by_key(|(spins, _)| *spins);
+        for ((i, j), interaction_strength) in logical_interactions {
+            if interaction_strength == 0.0 {
+                continue;
+            }
+            // The embedding was verified, so the chains of every pair of interacting spins are coupled
+            let couplers = &couplers_between_chains[&(i, j)];
+            for (left_physical, right_physical) in couplers {
+                physical_spin_network.interactions.push((
+                    *left_physical,
+                    *right_physical,
+                    interaction_strength / couplers.len() as InteractionStrength,
+                ));
+            }
+        }
+
+        Some(EmbeddedSpinNetwork {
+            spin_network: physical_spin_network,
+            qubits,
+            chains,
+            logical_interactions: self.interactions.clone(),
+            logical_external_magnetic_field: self.external_magnetic_field.clone(),
         })
-        .collect();
     }
     /// Returns the external magnetic field with flipped signs. The output of this function alongside `inverted_interactions`
     /// should be all that you need to find the ground state of this Spin Glass on a real quantum annealer.
     pub fn inverted_external_magnetic_field(&self) -> ExternalMagneticField {
-        return self.external_magnetic_field.iter().map(|&energy| -energy).collect();
+        self.external_magnetic_field.iter().map(|&energy| -energy).collect()
     }
     /// Returns the interaction terms flipped signs. The output of this function alongside `inverted_external_magnetic_field`
     /// should be all that you need to find the ground state of this Spin Glass on a real quantum annealer.
     pub fn inverted_interactions(&self) -> Interactions {
-        return self.interactions.iter().map(|(left_spin_index, right_spin_index, energy)| (*left_spin_index, *right_spin_index, -(*energy))).collect();
+        self.interactions.iter().map(|(left_spin_index, right_spin_index, energy)| (*left_spin_index, *right_spin_index, -(*energy))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodelib::logic_gates::AND;
+    use crate::spin_network::SpinNetwork;
+    use crate::types::{BinaryNode, Node, SpinIndex};
+
+    /// A node that only couples its inputs, without adding any spin.
+    struct Tie;
+    impl Node for Tie {
+        fn connect(&self, spin_network: &mut SpinNetwork) -> SpinIndex {
+            spin_network.external_magnetic_field.len() - 1
+        }
+    }
+    impl BinaryNode for Tie {
+        fn connect_to_two(
+            &self,
+            spin_network: &mut SpinNetwork,
+            left_input: SpinIndex,
+            right_input: SpinIndex,
+        ) -> SpinIndex {
+            spin_network
+                .interactions
+                .push((left_input, right_input, 1.0));
+
+            left_input
+        }
+    }
+
+    #[test]
+    fn test_logical_blocks_skip_nodes_without_spins() {
+        let mut spin_network = SpinNetwork::new();
+        let s0 = spin_network.add_input_node(0.0);
+        let s1 = spin_network.add_input_node(0.0);
+        let z_tie = spin_network.add_binary_node(s0, s1, &Tie);
+        let z = spin_network.add_binary_node(z_tie, s1, &AND::default());
+
+        let logical_blocks = spin_network.logical_blocks();
+
+        assert_eq!(1, logical_blocks.len());
+        assert_eq!(vec![s0, s1], logical_blocks[0].inputs);
+        assert!(logical_blocks[0].spins.contains(&z));
     }
 }
@@ -13,6 +13,10 @@ pub type MagneticFieldStrength = Energy;
 pub type ExternalMagneticField = Vec<MagneticFieldStrength>;
 pub type State = Vec<bool>;
 pub type CompactState = FixedBitSet;
+pub type Qubit = usize;
+pub type Coupler = (Qubit, Qubit);
+pub type Chain = Vec<Qubit>;
+pub type GridPosition = (usize, usize);
 
 /// A Node is anything that is able to connect itself to the spin network.
 pub trait Node {
@@ -30,5 +34,6 @@ pub trait BinaryNode: Node {
     ) -> SpinIndex;
 }
 pub trait NAryNode: Node {
+    #[allow(clippy::ptr_arg)]
     fn connect_to_n(&self, spin_network: &mut SpinNetwork, inputs: &Vec<SpinIndex>) -> SpinIndex;
 }